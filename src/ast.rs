@@ -0,0 +1,130 @@
+use std::cell::Cell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::tokenizer::Token;
+
+#[derive(Clone, Debug)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    Nil,
+}
+
+/// The index of the global a variable node refers to, filled in by the
+/// interpreter the first time the node runs.
+pub type GlobalSlot = Cell<Option<usize>>;
+
+/// How many scopes out from the reference a local was declared, filled in
+/// by the resolver. `None` means the variable is a global.
+pub type ScopeDepth = Cell<Option<usize>>;
+
+#[derive(Clone, Debug)]
+pub enum Expr {
+    Literal(Literal),
+    Grouping(Box<Expr>),
+    Unary {
+        op: Token,
+        right: Box<Expr>,
+    },
+    Binary {
+        left: Box<Expr>,
+        op: Token,
+        right: Box<Expr>,
+    },
+    /// `and` / `or`, which only evaluate the right side when they need it.
+    Logical {
+        left: Box<Expr>,
+        op: Token,
+        right: Box<Expr>,
+    },
+    Call {
+        callee: Box<Expr>,
+        /// The closing parenthesis, which runtime errors point at.
+        paren: Token,
+        arguments: Vec<Expr>,
+    },
+    Variable {
+        name: Token,
+        depth: ScopeDepth,
+        global: GlobalSlot,
+    },
+    Assign {
+        name: Token,
+        value: Box<Expr>,
+        depth: ScopeDepth,
+        global: GlobalSlot,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum Stmt {
+    Print(Expr),
+    Expression(Expr),
+    Var {
+        name: Token,
+        initializer: Option<Expr>,
+    },
+    Block(Vec<Stmt>),
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    /// Also what `for` loops are desugared into.
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+    },
+    /// Shared with the function values made from it.
+    Function(Rc<FunctionDecl>),
+    Return(Option<Expr>),
+}
+
+#[derive(Debug)]
+pub struct FunctionDecl {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // `{:?}` keeps the fractional part of whole numbers, so 1 prints
+            // as `1.0` like the book's AstPrinter.
+            Literal::Number(num) => write!(f, "{:?}", num),
+            Literal::String(s) => write!(f, "{}", s),
+            Literal::Bool(b) => write!(f, "{}", b),
+            Literal::Nil => write!(f, "nil"),
+        }
+    }
+}
+
+/// The parenthesized form printed by `parse`, e.g. `(+ 1.0 (group 2.0))`.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(literal) => write!(f, "{}", literal),
+            Expr::Grouping(inner) => write!(f, "(group {})", inner),
+            Expr::Unary { op, right } => write!(f, "({} {})", op.lexeme(), right),
+            Expr::Binary { left, op, right } | Expr::Logical { left, op, right } => {
+                write!(f, "({} {} {})", op.lexeme(), left, right)
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                write!(f, "(call {}", callee)?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
+                }
+                write!(f, ")")
+            }
+            Expr::Variable { name, .. } => write!(f, "{}", name.lexeme()),
+            Expr::Assign { name, value, .. } => {
+                write!(f, "(= {} {})", name.lexeme(), value)
+            }
+        }
+    }
+}
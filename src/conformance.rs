@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// The subcommand a fixture is run with, based on its file-name prefix
+/// (`scanning_number.lox` is a scanning fixture, and so on). Everything past
+/// parsing, `eval_` fixtures included, is a whole program and is run.
+fn command_for(category: &str) -> &'static str {
+    match category {
+        "scanning" => "tokenize",
+        "parsing" => "parse",
+        _ => "run",
+    }
+}
+
+fn category_of(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    match stem.split_once('_') {
+        Some((category, _)) => category.to_string(),
+        None => stem.to_string(),
+    }
+}
+
+fn fixtures(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lox"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+fn execute(binary: &Path, command: &str, file: &Path) -> Option<Output> {
+    Command::new(binary).arg(command).arg(file).output().ok()
+}
+
+/// Names the parts of two runs that differ, or returns an empty list.
+fn differences(ours: &Output, theirs: &Output) -> Vec<&'static str> {
+    let mut diffs = Vec::new();
+    if ours.stdout != theirs.stdout {
+        diffs.push("stdout");
+    }
+    if ours.stderr != theirs.stderr {
+        diffs.push("stderr");
+    }
+    if ours.status.code() != theirs.status.code() {
+        diffs.push("exit code");
+    }
+    diffs
+}
+
+/// Runs every fixture in `corpus` through this binary and `reference`,
+/// prints a per-category compatibility report and returns whether every
+/// fixture matched.
+pub fn run_conformance(reference: &str, corpus: &str) -> bool {
+    let ours = match env::current_exe() {
+        Ok(path) => path,
+        Err(_) => {
+            eprintln!("Cannot locate the interpreter binary");
+            return false;
+        }
+    };
+    let reference = Path::new(reference);
+    let files = fixtures(Path::new(corpus));
+    if files.is_empty() {
+        eprintln!("No .lox fixtures found in {}", corpus);
+        return false;
+    }
+
+    let mut report: BTreeMap<String, (usize, usize)> = BTreeMap::new();
+    for file in &files {
+        let category = category_of(file);
+        let command = command_for(&category);
+        let entry = report.entry(category).or_default();
+        entry.1 += 1;
+
+        let (Some(our_run), Some(their_run)) = (
+            execute(&ours, command, file),
+            execute(reference, command, file),
+        ) else {
+            println!("ERROR {}: could not run both interpreters", file.display());
+            continue;
+        };
+        let diffs = differences(&our_run, &their_run);
+        if diffs.is_empty() {
+            entry.0 += 1;
+        } else {
+            println!(
+                "FAIL {} ({}): {}",
+                file.display(),
+                command,
+                diffs.join(", ")
+            );
+        }
+    }
+
+    let mut passed_total = 0;
+    println!();
+    for (category, (passed, total)) in &report {
+        println!("{:<12} {}/{}", category, passed, total);
+        passed_total += passed;
+    }
+    println!("{:<12} {}/{}", "total", passed_total, files.len());
+    passed_total == files.len()
+}
@@ -0,0 +1,241 @@
+use std::cell::Cell;
+use std::fmt;
+
+use crate::json::{self, JsonValue};
+use crate::messages::{self, ErrorCompat, Lang, Message};
+use crate::source_map::{SourceId, SourceMap};
+use crate::style::{Color, Style};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// The token a parse error points at, for the book's `Error at ...` form.
+#[derive(Clone, Debug)]
+pub enum At {
+    Lexeme(String),
+    End,
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: Message,
+    pub line: usize,
+    pub column: Option<usize>,
+    pub at: Option<At>,
+    pub source: SourceId,
+}
+
+impl Diagnostic {
+    pub fn error(line: usize, message: Message) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message,
+            line,
+            column: None,
+            at: None,
+            source: SourceId::default(),
+        }
+    }
+
+    pub fn in_source(self, source: SourceId) -> Self {
+        Diagnostic { source, ..self }
+    }
+
+    pub fn with_column(self, column: usize) -> Self {
+        Diagnostic {
+            column: Some(column),
+            ..self
+        }
+    }
+
+    pub fn at(self, at: At) -> Self {
+        Diagnostic {
+            at: Some(at),
+            ..self
+        }
+    }
+
+    /// The message text; book mode is always English, like jlox.
+    fn text(&self, lang: Lang, compat: ErrorCompat) -> String {
+        match (compat, self.message.book_text()) {
+            (ErrorCompat::Book, Some(text)) => text,
+            (ErrorCompat::Book, None) => self.message.text(Lang::En),
+            (ErrorCompat::Default, _) => self.message.text(lang),
+        }
+    }
+
+    pub fn render(&self, lang: Lang, compat: ErrorCompat) -> String {
+        let lang = match compat {
+            ErrorCompat::Book => Lang::En,
+            ErrorCompat::Default => lang,
+        };
+        // jlox puts the line after the message for runtime errors.
+        if compat == ErrorCompat::Book && self.message.is_runtime() {
+            return format!("{}\n[line {}]", self.text(lang, compat), self.line);
+        }
+        let mut label = match self.severity {
+            Severity::Error => messages::error_label(lang),
+        }
+        .to_string();
+        match &self.at {
+            Some(At::Lexeme(lexeme)) => {
+                label = format!("{} {}", label, messages::at_lexeme(lang, lexeme))
+            }
+            Some(At::End) => label = format!("{} {}", label, messages::at_end(lang)),
+            None => {}
+        }
+        format!(
+            "[{} {}] {}: {}",
+            messages::line_label(lang),
+            self.line,
+            label,
+            self.text(lang, compat)
+        )
+    }
+
+    pub fn to_json_value(&self, file: &str, lang: Lang, compat: ErrorCompat) -> JsonValue {
+        json::object([
+            ("code", json::string(self.message.code())),
+            ("severity", json::string(self.severity.as_str())),
+            ("message", json::string(self.text(lang, compat))),
+            ("file", json::string(file)),
+            (
+                "span",
+                json::object([
+                    ("line", JsonValue::Number(self.line as f64)),
+                    (
+                        "column",
+                        self.column
+                            .map_or(JsonValue::Null, |column| JsonValue::Number(column as f64)),
+                    ),
+                ]),
+            ),
+        ])
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Lang::En, ErrorCompat::Default))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Writes diagnostics to stderr in the format selected on the command line,
+/// showing at most `max_errors` of them.
+pub struct Reporter<'a> {
+    pub style: Style,
+    pub format: ErrorFormat,
+    pub sources: &'a SourceMap,
+    pub lang: Lang,
+    pub compat: ErrorCompat,
+    max_errors: Option<usize>,
+    seen: Cell<usize>,
+}
+
+impl<'a> Reporter<'a> {
+    pub fn new(
+        style: Style,
+        format: ErrorFormat,
+        sources: &'a SourceMap,
+        lang: Lang,
+        compat: ErrorCompat,
+        max_errors: Option<usize>,
+    ) -> Self {
+        Reporter {
+            style,
+            format,
+            sources,
+            lang,
+            compat,
+            max_errors,
+            seen: Cell::new(0),
+        }
+    }
+
+    /// Counts one more diagnostic and says whether it is still within the
+    /// `--max-errors` budget and should be shown.
+    pub fn admit(&self) -> bool {
+        self.seen.set(self.seen.get() + 1);
+        self.max_errors.map_or(true, |max| self.seen.get() <= max)
+    }
+
+    pub fn report(&self, diagnostic: &Diagnostic) {
+        if !self.admit() {
+            return;
+        }
+        match self.format {
+            ErrorFormat::Human => {
+                let color = match diagnostic.severity {
+                    Severity::Error => Color::Red,
+                };
+                let text = self.render(diagnostic);
+                eprintln!("{}", self.style.paint_stderr(color, &text));
+            }
+            ErrorFormat::Json => {
+                let file = self.sources.path(diagnostic.source);
+                eprintln!("{}", diagnostic.to_json_value(file, self.lang, self.compat));
+            }
+        }
+    }
+
+    /// The human form of a diagnostic. Once a run involves more than one
+    /// file, the message is prefixed with the file it came from.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let text = diagnostic.render(self.lang, self.compat);
+        if self.sources.len() > 1 {
+            format!("{}: {}", self.sources.path(diagnostic.source), text)
+        } else {
+            text
+        }
+    }
+
+    /// Prints how many diagnostics were held back by `--max-errors`. In
+    /// JSON mode the count is one more object on its own line, with a
+    /// `hidden` field instead of a `code`.
+    pub fn finish(&self) {
+        let hidden = self
+            .max_errors
+            .map_or(0, |max| self.seen.get().saturating_sub(max));
+        if hidden == 0 {
+            return;
+        }
+        let text = messages::hidden_errors(self.lang, hidden);
+        match self.format {
+            ErrorFormat::Human => eprintln!("{}", text),
+            ErrorFormat::Json => {
+                let summary = json::object([
+                    ("hidden", JsonValue::Number(hidden as f64)),
+                    ("message", json::string(text)),
+                ]);
+                eprintln!("{}", summary);
+            }
+        }
+    }
+}
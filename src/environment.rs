@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::value::Value;
+
+/// One local scope's variables. Each block and call gets its own
+/// environment, whose parent is the scope it appears in; globals are kept
+/// apart in `Globals`. Environments are shared: a function keeps the scope
+/// it was declared in alive for as long as the function itself exists.
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    /// A new scope nested in `enclosing`, or directly in the globals.
+    pub fn with_enclosing(enclosing: Option<Rc<RefCell<Environment>>>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Environment {
+            values: HashMap::new(),
+            enclosing,
+        }))
+    }
+
+    /// Declares a variable in this scope. Redeclaring a name replaces it,
+    /// and a name declared here shadows the same name in parent scopes.
+    pub fn define(&mut self, name: String, value: Value) {
+        self.values.insert(name, value);
+    }
+
+    /// The scope `distance` steps out from `scope`, as the resolver counted.
+    fn ancestor(scope: &Rc<RefCell<Environment>>, distance: usize) -> Option<Rc<RefCell<Self>>> {
+        let mut scope = Rc::clone(scope);
+        for _ in 0..distance {
+            let enclosing = scope.borrow().enclosing.clone()?;
+            scope = enclosing;
+        }
+        Some(scope)
+    }
+
+    pub fn get_at(scope: &Rc<RefCell<Environment>>, distance: usize, name: &str) -> Option<Value> {
+        let ancestor = Self::ancestor(scope, distance)?;
+        let value = ancestor.borrow().values.get(name).cloned();
+        value
+    }
+
+    /// Returns false if the resolved scope does not hold `name`.
+    pub fn assign_at(
+        scope: &Rc<RefCell<Environment>>,
+        distance: usize,
+        name: &str,
+        value: Value,
+    ) -> bool {
+        let Some(ancestor) = Self::ancestor(scope, distance) else {
+            return false;
+        };
+        let mut ancestor = ancestor.borrow_mut();
+        match ancestor.values.get_mut(name) {
+            Some(slot) => {
+                *slot = value;
+                true
+            }
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,155 @@
+/// Emits random, well-formed Lox programs for differential testing against
+/// other implementations. Every program terminates: loops count up to a small
+/// bound, functions never recurse, and there is no I/O besides `print`.
+/// Variables only ever hold numbers, so the programs are free of runtime
+/// type errors as well.
+pub struct Generator {
+    state: u64,
+    globals: Vec<String>,
+    functions: Vec<(String, usize)>,
+    loop_counter: usize,
+}
+
+const MAX_DEPTH: usize = 3;
+const STATEMENTS: usize = 12;
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            // xorshift gets stuck on zero, so nudge the seed away from it.
+            state: seed ^ 0x9E37_79B9_7F4A_7C15,
+            globals: Vec::new(),
+            functions: Vec::new(),
+            loop_counter: 0,
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    pub fn program(&mut self) -> String {
+        let mut out = String::new();
+        for i in 0..2 + self.below(3) {
+            let name = format!("v{}", i);
+            let init = self.below(10);
+            out.push_str(&format!("var {} = {};\n", name, init));
+            self.globals.push(name);
+        }
+        for i in 0..self.below(3) {
+            let name = format!("f{}", i);
+            let params = 1 + self.below(2);
+            let names: Vec<String> = (0..params).map(|p| format!("p{}", p)).collect();
+            let body = self.expression(&names, 0);
+            out.push_str(&format!(
+                "fun {}({}) {{\n  return {};\n}}\n",
+                name,
+                names.join(", "),
+                body
+            ));
+            self.functions.push((name, params));
+        }
+        for _ in 0..STATEMENTS {
+            let stmt = self.statement(0);
+            out.push_str(&stmt);
+        }
+        out
+    }
+
+    fn statement(&mut self, depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+        let globals = self.globals.clone();
+        let kind = if depth >= MAX_DEPTH {
+            self.below(2)
+        } else {
+            self.below(5)
+        };
+        match kind {
+            0 => format!("{}print {};\n", indent, self.expression(&globals, 0)),
+            1 => {
+                let target = globals[self.below(globals.len())].clone();
+                let op = ["+", "-"][self.below(2)];
+                let amount = self.below(5);
+                format!("{}{} = {} {} {};\n", indent, target, target, op, amount)
+            }
+            2 => {
+                let cond = self.condition(&globals);
+                let then_branch = self.block(depth);
+                if self.below(2) == 0 {
+                    format!("{}if ({}) {}\n", indent, cond, then_branch)
+                } else {
+                    let else_branch = self.block(depth);
+                    format!(
+                        "{}if ({}) {} else {}\n",
+                        indent, cond, then_branch, else_branch
+                    )
+                }
+            }
+            3 => {
+                let counter = format!("i{}", self.loop_counter);
+                self.loop_counter += 1;
+                let bound = 1 + self.below(4);
+                let body = self.block(depth);
+                format!(
+                    "{}for (var {} = 0; {} < {}; {} = {} + 1) {}\n",
+                    indent, counter, counter, bound, counter, counter, body
+                )
+            }
+            _ => format!("{}{{\n{}{}}}\n", indent, self.statement(depth + 1), indent),
+        }
+    }
+
+    fn block(&mut self, depth: usize) -> String {
+        let indent = "  ".repeat(depth);
+        let mut body = String::new();
+        for _ in 0..1 + self.below(2) {
+            body.push_str(&self.statement(depth + 1));
+        }
+        format!("{{\n{}{}}}", body, indent)
+    }
+
+    fn condition(&mut self, names: &[String]) -> String {
+        let op = ["<", "<=", ">", ">=", "==", "!="][self.below(6)];
+        let left = self.expression(names, 1);
+        let right = self.expression(names, 1);
+        format!("{} {} {}", left, op, right)
+    }
+
+    fn expression(&mut self, names: &[String], depth: usize) -> String {
+        let leaf = depth >= MAX_DEPTH || self.below(3) == 0;
+        if leaf {
+            return match self.below(3) {
+                0 if !names.is_empty() => names[self.below(names.len())].clone(),
+                _ => self.below(20).to_string(),
+            };
+        }
+        match self.below(4) {
+            0 if !self.functions.is_empty() && names == self.globals.as_slice() => {
+                let pick = self.below(self.functions.len());
+                let (name, arity) = self.functions[pick].clone();
+                let args: Vec<String> = (0..arity)
+                    .map(|_| self.expression(names, depth + 1))
+                    .collect();
+                format!("{}({})", name, args.join(", "))
+            }
+            1 => format!("-{}", self.expression(names, depth + 1)),
+            _ => {
+                let op = ["+", "-", "*"][self.below(3)];
+                let left = self.expression(names, depth + 1);
+                let right = self.expression(names, depth + 1);
+                format!("({} {} {})", left, op, right)
+            }
+        }
+    }
+}
+
+pub fn run_generator(seed: u64) {
+    print!("{}", Generator::new(seed).program());
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+use crate::value::Value;
+
+/// The global scope, stored by index. A name is interned the first time it
+/// is defined or looked up, and the variable node that looked it up caches
+/// the index, so later runs of that node skip hashing the name.
+#[derive(Default)]
+pub struct Globals {
+    indices: HashMap<String, usize>,
+    /// `None` for names that have been looked up but never defined.
+    values: Vec<Option<Value>>,
+}
+
+impl Globals {
+    pub fn intern(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.indices.get(name) {
+            return index;
+        }
+        self.values.push(None);
+        self.indices.insert(name.to_string(), self.values.len() - 1);
+        self.values.len() - 1
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        let index = self.intern(name);
+        self.values[index] = Some(value);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Value> {
+        self.values.get(index).and_then(Option::as_ref)
+    }
+
+    /// Assigns to a defined global; returns false if it is not defined.
+    pub fn set(&mut self, index: usize, value: Value) -> bool {
+        match self.values.get_mut(index) {
+            Some(slot @ Some(_)) => {
+                *slot = Some(value);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,461 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ast::{Expr, FunctionDecl, GlobalSlot, ScopeDepth, Stmt};
+use crate::diagnostic::{Diagnostic, Reporter};
+use crate::environment::Environment;
+use crate::globals::Globals;
+use crate::line_index::LineIndex;
+use crate::messages::Message;
+use crate::parser::{self, Parser};
+use crate::resolver::Resolver;
+use crate::tokenizer::{KeywordAliases, ReservedWords, ScanLimits, Token, TokenType};
+use crate::value::{Callable, Value};
+
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub token: Token,
+    pub message: Message,
+}
+
+impl RuntimeError {
+    pub fn new(token: &Token, message: Message) -> Self {
+        RuntimeError {
+            token: token.clone(),
+            message,
+        }
+    }
+
+    pub fn diagnostic(&self, lines: &LineIndex) -> Diagnostic {
+        let (_, column) = lines.offset_to_line_col(self.token.offset);
+        Diagnostic::error(self.token.line, self.message.clone())
+            .in_source(self.token.source)
+            .with_column(column)
+    }
+}
+
+/// Why execution of a statement stopped early. `return` travels up to the
+/// call that is returning the same way an error would.
+#[derive(Debug)]
+pub enum Unwind {
+    Return(Value),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind {
+    fn from(error: RuntimeError) -> Self {
+        Unwind::Error(error)
+    }
+}
+
+/// Tree-walking interpreter.
+pub struct Interpreter {
+    globals: Globals,
+    /// The innermost local scope, or `None` in top-level code.
+    environment: Option<Rc<RefCell<Environment>>>,
+    /// How many Lox function calls are running.
+    calls: usize,
+    /// The address of a local in the frame that created the interpreter,
+    /// which is how far up the stack its calls start.
+    stack_base: usize,
+}
+
+/// How many Lox calls may be running at once, far deeper than ordinary
+/// recursion needs. Each one recurses on the native stack, so deeper
+/// programs fail with a runtime error instead.
+const MAX_CALL_DEPTH: usize = 10_000;
+
+/// The native stack `run` gives the interpreter. A debug build fits about
+/// 90k simple calls, taking about 11 KiB each. A body nested as deeply as the
+/// parser allows can take over 1 MiB a call; those calls are stopped by
+/// `STACK_RESERVE` instead.
+pub const STACK_SIZE: usize = 1 << 30;
+
+/// Stack kept free below the deepest call, enough for one more call at the
+/// deepest nesting the parser allows, with plenty of room to spare.
+const STACK_RESERVE: usize = 64 << 20;
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        let mut globals = Globals::default();
+        globals.define(
+            "clock",
+            Value::Callable(Rc::new(Callable::Native {
+                arity: 0,
+                function: clock,
+            })),
+        );
+        let base = 0u8;
+        Interpreter {
+            globals,
+            environment: None,
+            calls: 0,
+            stack_base: std::ptr::addr_of!(base) as usize,
+        }
+    }
+}
+
+/// Seconds since the Unix epoch.
+fn clock(_arguments: &[Value]) -> Value {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Value::Number(elapsed.as_secs_f64())
+}
+
+impl Interpreter {
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        match self.execute_all(statements) {
+            // The parser only accepts `return` inside a function.
+            Ok(()) | Err(Unwind::Return(_)) => Ok(()),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+
+    fn execute_all(&mut self, statements: &[Stmt]) -> Result<(), Unwind> {
+        for stmt in statements {
+            self.execute(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> Result<(), Unwind> {
+        match stmt {
+            Stmt::Print(expr) => {
+                let value = self.evaluate(expr)?;
+                println!("{}", value);
+            }
+            Stmt::Expression(expr) => {
+                self.evaluate(expr)?;
+            }
+            Stmt::Var { name, initializer } => {
+                let value = match initializer {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                self.define(name, value);
+            }
+            Stmt::Block(statements) => self.execute_block(statements)?,
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    self.execute(then_branch)?;
+                } else if let Some(else_branch) = else_branch {
+                    self.execute(else_branch)?;
+                }
+            }
+            Stmt::While { condition, body } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    self.execute(body)?;
+                }
+            }
+            Stmt::Function(decl) => {
+                let function = Value::Callable(Rc::new(Callable::Function {
+                    decl: Rc::clone(decl),
+                    closure: self.environment.clone(),
+                }));
+                self.define(&decl.name, function);
+            }
+            Stmt::Return(value) => {
+                let value = match value {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::Nil,
+                };
+                return Err(Unwind::Return(value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs a block in a new scope nested in the current one. The scope is
+    /// closed again even when a statement fails.
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<(), Unwind> {
+        let scope = Environment::with_enclosing(self.environment.clone());
+        self.execute_in(scope, statements)
+    }
+
+    fn define(&mut self, name: &Token, value: Value) {
+        match &self.environment {
+            Some(scope) => scope
+                .borrow_mut()
+                .define(name.identifier().to_string(), value),
+            None => self.globals.define(name.identifier(), value),
+        }
+    }
+
+    /// Locals are read from the scope the resolver found them in; anything
+    /// else is a global, reached through the node's cached index.
+    fn look_up(
+        &mut self,
+        name: &Token,
+        depth: &ScopeDepth,
+        global: &GlobalSlot,
+    ) -> Result<Value, RuntimeError> {
+        if let (Some(distance), Some(scope)) = (depth.get(), &self.environment) {
+            return Environment::get_at(scope, distance, name.identifier())
+                .ok_or_else(|| undefined(name));
+        }
+        let index = self.global_index(name, global);
+        self.globals
+            .get(index)
+            .cloned()
+            .ok_or_else(|| undefined(name))
+    }
+
+    fn assign(
+        &mut self,
+        name: &Token,
+        depth: &ScopeDepth,
+        global: &GlobalSlot,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        if let (Some(distance), Some(scope)) = (depth.get(), &self.environment) {
+            return match Environment::assign_at(scope, distance, name.identifier(), value) {
+                true => Ok(()),
+                false => Err(undefined(name)),
+            };
+        }
+        let index = self.global_index(name, global);
+        if self.globals.set(index, value) {
+            Ok(())
+        } else {
+            Err(undefined(name))
+        }
+    }
+
+    fn global_index(&mut self, name: &Token, global: &GlobalSlot) -> usize {
+        match global.get() {
+            Some(index) => index,
+            None => {
+                let index = self.globals.intern(name.identifier());
+                global.set(Some(index));
+                index
+            }
+        }
+    }
+
+    /// Runs statements with `scope` as the innermost scope, going back to
+    /// the current one afterwards.
+    fn execute_in(
+        &mut self,
+        scope: Rc<RefCell<Environment>>,
+        statements: &[Stmt],
+    ) -> Result<(), Unwind> {
+        let previous = self.environment.replace(scope);
+        let result = self.execute_all(statements);
+        self.environment = previous;
+        result
+    }
+
+    fn call(
+        &mut self,
+        callee: Value,
+        paren: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let Value::Callable(callable) = callee else {
+            return Err(RuntimeError::new(paren, Message::NotCallable));
+        };
+        if arguments.len() != callable.arity() {
+            let message = Message::ArityMismatch {
+                expected: callable.arity(),
+                got: arguments.len(),
+            };
+            return Err(RuntimeError::new(paren, message));
+        }
+        match callable.as_ref() {
+            Callable::Native { function, .. } => Ok(function(&arguments)),
+            Callable::Function { decl, closure } => {
+                if self.calls == MAX_CALL_DEPTH || self.stack_used() > STACK_SIZE - STACK_RESERVE {
+                    return Err(RuntimeError::new(paren, Message::StackOverflow));
+                }
+                self.calls += 1;
+                let result = self.call_function(decl, closure, arguments);
+                self.calls -= 1;
+                result
+            }
+        }
+    }
+
+    /// How much native stack the calls under `interpret` use so far. The
+    /// interpreter must run on a thread with `STACK_SIZE` of stack.
+    fn stack_used(&self) -> usize {
+        let here = 0u8;
+        self.stack_base.abs_diff(std::ptr::addr_of!(here) as usize)
+    }
+
+    /// Runs a function body in a new scope holding its parameters, nested in
+    /// the scope the function was declared in rather than the caller's.
+    fn call_function(
+        &mut self,
+        decl: &FunctionDecl,
+        closure: &Option<Rc<RefCell<Environment>>>,
+        arguments: Vec<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let scope = Environment::with_enclosing(closure.clone());
+        for (param, argument) in decl.params.iter().zip(arguments) {
+            scope
+                .borrow_mut()
+                .define(param.identifier().to_string(), argument);
+        }
+        match self.execute_in(scope, &decl.body) {
+            Ok(()) => Ok(Value::Nil),
+            Err(Unwind::Return(value)) => Ok(value),
+            Err(Unwind::Error(error)) => Err(error),
+        }
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        match expr {
+            Expr::Literal(literal) => Ok(Value::from(literal)),
+            Expr::Grouping(inner) => self.evaluate(inner),
+            Expr::Unary { op, right } => {
+                let right = self.evaluate(right)?;
+                match (&op.token_type, right) {
+                    (TokenType::Bang, right) => Ok(Value::Bool(!right.is_truthy())),
+                    (TokenType::Minus, Value::Number(num)) => Ok(Value::Number(-num)),
+                    _ => Err(RuntimeError::new(op, Message::OperandMustBeNumber)),
+                }
+            }
+            Expr::Binary { left, op, right } => {
+                let left = self.evaluate(left)?;
+                let right = self.evaluate(right)?;
+                binary(op, left, right)
+            }
+            Expr::Logical { left, op, right } => {
+                let left = self.evaluate(left)?;
+                let short_circuits = match op.token_type {
+                    TokenType::Reserved(ReservedWords::Or) => left.is_truthy(),
+                    _ => !left.is_truthy(),
+                };
+                if short_circuits {
+                    Ok(left)
+                } else {
+                    self.evaluate(right)
+                }
+            }
+            Expr::Call {
+                callee,
+                paren,
+                arguments,
+            } => {
+                let callee = self.evaluate(callee)?;
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<_>, _>>()?;
+                self.call(callee, paren, arguments)
+            }
+            Expr::Variable {
+                name,
+                depth,
+                global,
+            } => self.look_up(name, depth, global),
+            Expr::Assign {
+                name,
+                value,
+                depth,
+                global,
+            } => {
+                let value = self.evaluate(value)?;
+                self.assign(name, depth, global, value.clone())?;
+                Ok(value)
+            }
+        }
+    }
+}
+
+fn undefined(name: &Token) -> RuntimeError {
+    RuntimeError::new(
+        name,
+        Message::UndefinedVariable(name.identifier().to_string()),
+    )
+}
+
+fn binary(op: &Token, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    let value = match (&op.token_type, left, right) {
+        (TokenType::DoubleEquals, left, right) => Value::Bool(left == right),
+        (TokenType::BangEquals, left, right) => Value::Bool(left != right),
+        (TokenType::Plus, Value::Number(a), Value::Number(b)) => Value::Number(a + b),
+        (TokenType::Plus, Value::String(a), Value::String(b)) => Value::String(a + &b),
+        (TokenType::Plus, _, _) => {
+            return Err(RuntimeError::new(
+                op,
+                Message::OperandsMustBeNumbersOrStrings,
+            ))
+        }
+        (TokenType::Minus, Value::Number(a), Value::Number(b)) => Value::Number(a - b),
+        (TokenType::Star, Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+        (TokenType::Slash, Value::Number(a), Value::Number(b)) => Value::Number(a / b),
+        (TokenType::Greater, Value::Number(a), Value::Number(b)) => Value::Bool(a > b),
+        (TokenType::GreaterEquals, Value::Number(a), Value::Number(b)) => Value::Bool(a >= b),
+        (TokenType::LessThan, Value::Number(a), Value::Number(b)) => Value::Bool(a < b),
+        (TokenType::LessThanEquals, Value::Number(a), Value::Number(b)) => Value::Bool(a <= b),
+        _ => return Err(RuntimeError::new(op, Message::OperandsMustBeNumbers)),
+    };
+    Ok(value)
+}
+
+/// Why `run` failed, which decides the exit code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunFailure {
+    /// Lexical or syntax errors; nothing was executed.
+    Compile,
+    Runtime,
+}
+
+impl RunFailure {
+    /// The sysexits codes jlox uses: EX_DATAERR and EX_SOFTWARE.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            RunFailure::Compile => 65,
+            RunFailure::Runtime => 70,
+        }
+    }
+}
+
+/// `run`: executes each file as a program of its own.
+pub fn run_program(
+    reporter: &Reporter,
+    aliases: &KeywordAliases,
+    limits: ScanLimits,
+) -> Result<(), RunFailure> {
+    let mut outcome = Ok(());
+    for (id, file) in reporter.sources.iter() {
+        let lines = LineIndex::new(&file.text);
+        let (tokens, lexical_errors) = parser::scan_for_parser(reporter, id, file, aliases, limits);
+        let statements = match Parser::new(tokens).parse_program() {
+            Ok(_) if lexical_errors => {
+                outcome = Err(RunFailure::Compile);
+                continue;
+            }
+            Ok(statements) => statements,
+            Err(errors) => {
+                for error in errors {
+                    reporter.report(&error.diagnostic(&lines));
+                }
+                outcome = Err(RunFailure::Compile);
+                continue;
+            }
+        };
+        if let Err(errors) = Resolver::default().resolve(&statements) {
+            for error in errors {
+                reporter.report(&error.diagnostic(&lines));
+            }
+            outcome = Err(RunFailure::Compile);
+            continue;
+        }
+        if let Err(error) = Interpreter::default().interpret(&statements) {
+            reporter.report(&error.diagnostic(&lines));
+            if outcome.is_ok() {
+                outcome = Err(RunFailure::Runtime);
+            }
+        }
+    }
+    reporter.finish();
+    outcome
+}
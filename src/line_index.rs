@@ -0,0 +1,172 @@
+/// Maps byte offsets in a source file to 1-based line/column positions and
+/// back. Built once per source so diagnostics don't rescan the text to find
+/// where something is.
+///
+/// Columns count characters, not bytes, so a position after `"é"` is one
+/// column further along rather than two.
+pub struct LineIndex<'a> {
+    source: &'a str,
+    /// Byte offset at which each line starts; `line_starts[0]` is always 0.
+    line_starts: Vec<usize>,
+    /// Whether each line is plain ASCII, where a column is just the byte
+    /// distance from the start of the line and needs no counting.
+    ascii: Vec<bool>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            source
+                .char_indices()
+                .filter(|&(_, ch)| ch == '\n')
+                .map(|(offset, _)| offset + 1),
+        );
+        let ascii = line_starts
+            .iter()
+            .enumerate()
+            .map(|(line, &start)| {
+                let end = line_starts.get(line + 1).map_or(source.len(), |&next| next);
+                source.as_bytes()[start..end].is_ascii()
+            })
+            .collect();
+        LineIndex {
+            source,
+            line_starts,
+            ascii,
+        }
+    }
+
+    /// Offsets past the end of the source map to the end of the last line.
+    pub fn offset_to_line_col(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.source.len());
+        let line = self.line_of(offset);
+        let start = self.line_starts[line];
+        (line + 1, self.count_columns(line, start, offset) + 1)
+    }
+
+    /// A cursor for looking up the columns of many offsets in order, such as
+    /// every token of a file.
+    pub fn cursor(&self) -> ColumnCursor<'_, 'a> {
+        ColumnCursor {
+            lines: self,
+            line: 0,
+            offset: 0,
+            column: 1,
+        }
+    }
+
+    /// The 0-based line holding `offset`.
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// How many columns lie between two offsets on the same line.
+    fn count_columns(&self, line: usize, from: usize, to: usize) -> usize {
+        match self.source.get(from..to) {
+            Some(text) if !self.ascii[line] => text.chars().count(),
+            _ => to - from,
+        }
+    }
+
+    /// Returns `None` when the line does not exist or the column is past the
+    /// end of that line.
+    pub fn line_col_to_offset(&self, line: usize, column: usize) -> Option<usize> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+        let text = &self.source[start..end];
+        let column = column.checked_sub(1)?;
+        match text.char_indices().nth(column) {
+            Some((offset, _)) => Some(start + offset),
+            None if column == text.chars().count() => Some(end),
+            None => None,
+        }
+    }
+}
+
+/// Columns for offsets visited in increasing order. Each lookup only counts
+/// the text since the previous one, so a whole file costs one pass over it
+/// even when its lines are long and not ASCII.
+pub struct ColumnCursor<'i, 'a> {
+    lines: &'i LineIndex<'a>,
+    line: usize,
+    offset: usize,
+    column: usize,
+}
+
+impl ColumnCursor<'_, '_> {
+    /// The 1-based column of `offset`. Going backwards is allowed but starts
+    /// the count over from the beginning of the line.
+    pub fn column(&mut self, offset: usize) -> usize {
+        let offset = offset.min(self.lines.source.len());
+        let line = self.lines.line_of(offset);
+        if line != self.line || offset < self.offset {
+            self.line = line;
+            self.offset = self.lines.line_starts[line];
+            self.column = 1;
+        }
+        self.column += self.lines.count_columns(line, self.offset, offset);
+        self.offset = offset;
+        self.column
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_character_boundary() {
+        let source = "var é = \"ü\";\n\nprint é;\n";
+        let lines = LineIndex::new(source);
+        for (offset, _) in source.char_indices() {
+            let (line, column) = lines.offset_to_line_col(offset);
+            assert_eq!(lines.line_col_to_offset(line, column), Some(offset));
+        }
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let lines = LineIndex::new("é=1\nab");
+        assert_eq!(lines.offset_to_line_col(2), (1, 2));
+        assert_eq!(lines.offset_to_line_col(5), (2, 1));
+        assert_eq!(lines.line_col_to_offset(1, 3), Some(3));
+    }
+
+    #[test]
+    fn clamps_offsets_past_the_end() {
+        let lines = LineIndex::new("ab\ncd");
+        assert_eq!(lines.offset_to_line_col(100), (2, 3));
+        assert_eq!(LineIndex::new("").offset_to_line_col(0), (1, 1));
+    }
+
+    #[test]
+    fn allows_the_column_just_past_a_line() {
+        let lines = LineIndex::new("ab\ncd\n");
+        assert_eq!(lines.line_col_to_offset(1, 3), Some(2));
+        assert_eq!(lines.line_col_to_offset(3, 1), Some(6));
+        assert_eq!(lines.line_col_to_offset(1, 4), None);
+    }
+
+    #[test]
+    fn cursor_agrees_with_direct_lookups() {
+        let source = "é = 1;\nprint \"ü\" + é;\n\nx";
+        let lines = LineIndex::new(source);
+        let mut cursor = lines.cursor();
+        let offsets: Vec<usize> = source.char_indices().map(|(offset, _)| offset).collect();
+        for &offset in offsets.iter().chain([3, 0, source.len() + 5].iter()) {
+            assert_eq!(cursor.column(offset), lines.offset_to_line_col(offset).1);
+        }
+    }
+
+    #[test]
+    fn rejects_missing_lines_and_zero_positions() {
+        let lines = LineIndex::new("ab\ncd");
+        assert_eq!(lines.line_col_to_offset(0, 1), None);
+        assert_eq!(lines.line_col_to_offset(1, 0), None);
+        assert_eq!(lines.line_col_to_offset(3, 1), None);
+    }
+}
@@ -1,31 +1,335 @@
 use std::env;
 use std::fs;
+use std::process;
+use std::thread;
+mod ast;
+mod conformance;
+mod diagnostic;
+mod environment;
+mod generator;
+mod globals;
+mod interpreter;
+mod json;
+mod line_index;
+mod messages;
 mod parser;
+mod resolver;
+mod serve;
+mod snippet;
+mod source_map;
+mod stats;
+mod style;
 mod tokenizer;
+mod value;
+
+fn usage(program: &str) {
+    eprintln!("Usage:");
+    eprintln!("  {} tokenize <file.lox>...", program);
+    eprintln!("  {} evaluate <file.lox>", program);
+    eprintln!("  {} parse <file.lox>", program);
+    eprintln!("  {} run <file.lox>", program);
+    eprintln!("  {} stats <file.lox>...", program);
+    eprintln!("  {} gen [--seed N]", program);
+    eprintln!("  {} explain <code>", program);
+    eprintln!("  {} serve --stdio", program);
+    eprintln!(
+        "  {} serve --http <addr>    (with the `server` feature)",
+        program
+    );
+    eprintln!("  {} snippet export <command> <file.lox>", program);
+    eprintln!("  {} snippet import <file>", program);
+    eprintln!("  {} conformance --reference <binary> [dir]", program);
+    eprintln!("Options:");
+    eprintln!("  --no-color                  disable colored output");
+    eprintln!("  --error-format=human|json   how diagnostics are printed");
+    eprintln!("  --error-compat=book         jlox's exact error wording");
+    eprintln!("  --max-errors=N              show at most N errors");
+    eprintln!("  --max-identifier-length=N   reject longer identifiers (default 1024)");
+    eprintln!("  --max-literal-length=N      reject longer literals, in bytes (default 16 MiB)");
+    eprintln!("  --max-tokens=N              stop scanning after N tokens (default 2,000,000)");
+    eprintln!("  --keyword-aliases=<file>    extra keyword spellings (alias = keyword)");
+    eprintln!("  --lang=en|es                diagnostic language (default: $LOX_LANG)");
+}
+
+/// Parses a numeric option value, exiting with a message if it is invalid.
+fn numeric_option<T: std::str::FromStr>(name: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| {
+        eprintln!("Invalid value for {}: {}", name, value);
+        process::exit(2);
+    })
+}
+
+/// Returns the value of an option given either as `--name=value` or as
+/// `--name value`.
+fn option_value<'a>(
+    inline: Option<&'a str>,
+    rest: &mut impl Iterator<Item = &'a String>,
+) -> Option<&'a str> {
+    inline.or_else(|| rest.next().map(String::as_str))
+}
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 3 {
-        eprintln!("Usage:");
-        eprintln!("  {} tokenize <file.lox>", args[0]);
-        eprintln!("  {} evaluate <file.lox>", args[0]);
-        eprintln!("  {} parse <file.lox>", args[0]);
+    let mut positional: Vec<&String> = Vec::new();
+    let mut no_color = false;
+    let mut error_format = diagnostic::ErrorFormat::Human;
+    let mut seed: u64 = 0;
+    let mut reference: Option<&str> = None;
+    let mut max_errors: Option<usize> = None;
+    let mut stdio = false;
+    let mut http: Option<&str> = None;
+    // Options that change a command's output, recorded by `snippet export`.
+    let mut replay_flags: Vec<String> = Vec::new();
+    let mut aliases_file: Option<&str> = None;
+    let mut error_compat = messages::ErrorCompat::Default;
+    let mut limits = tokenizer::ScanLimits::default();
+    let mut lang = env::var("LOX_LANG")
+        .ok()
+        .and_then(|name| messages::Lang::parse(&name))
+        .unwrap_or(messages::Lang::En);
+
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
+        if !arg.starts_with("--") {
+            positional.push(arg);
+            continue;
+        }
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        match name {
+            "--no-color" => no_color = true,
+            "--stdio" => stdio = true,
+            "--http" => http = option_value(inline, &mut rest),
+            "--error-format" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                replay_flags.push(format!("--error-format={}", value));
+                match diagnostic::ErrorFormat::parse(value) {
+                    Some(format) => error_format = format,
+                    None => {
+                        eprintln!("Unknown error format: {}", value);
+                        process::exit(2);
+                    }
+                }
+            }
+            "--error-compat" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                replay_flags.push(format!("--error-compat={}", value));
+                match messages::ErrorCompat::parse(value) {
+                    Some(compat) => error_compat = compat,
+                    None => {
+                        eprintln!("Unknown error compatibility mode: {}", value);
+                        process::exit(2);
+                    }
+                }
+            }
+            "--max-identifier-length" | "--max-literal-length" | "--max-tokens" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                replay_flags.push(format!("{}={}", name, value));
+                let value = numeric_option(name, value);
+                match name {
+                    "--max-identifier-length" => limits.max_identifier_len = value,
+                    "--max-literal-length" => limits.max_literal_len = value,
+                    _ => limits.max_tokens = value,
+                }
+            }
+            "--seed" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                match value.parse() {
+                    Ok(value) => seed = value,
+                    Err(_) => {
+                        eprintln!("Invalid seed: {}", value);
+                        process::exit(2);
+                    }
+                }
+            }
+            "--max-errors" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                replay_flags.push(format!("--max-errors={}", value));
+                match value.parse() {
+                    Ok(value) => max_errors = Some(value),
+                    Err(_) => {
+                        eprintln!("Invalid error limit: {}", value);
+                        process::exit(2);
+                    }
+                }
+            }
+            "--lang" => {
+                let value = option_value(inline, &mut rest).unwrap_or_default();
+                match messages::Lang::parse(value) {
+                    Some(value) => lang = value,
+                    None => {
+                        eprintln!("Unsupported language: {}", value);
+                        process::exit(2);
+                    }
+                }
+            }
+            "--keyword-aliases" => aliases_file = option_value(inline, &mut rest),
+            "--reference" => reference = option_value(inline, &mut rest),
+            _ => {
+                eprintln!("Unknown option: {}", arg);
+                process::exit(2);
+            }
+        }
+    }
+
+    let Some(command) = positional.first() else {
+        usage(&args[0]);
+        process::exit(2);
+    };
+    if command.as_str() == "gen" {
+        generator::run_generator(seed);
+        return;
+    }
+    if command.as_str() == "explain" {
+        let code = positional.get(1).map_or("", |code| code.as_str());
+        match messages::explanation(code) {
+            Some(text) => print!("{}", text),
+            None => {
+                eprintln!("No explanation for diagnostic code: {}", code);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if command.as_str() == "snippet" {
+        let action = positional.get(1).map_or("", |action| action.as_str());
+        let result = match (action, positional.get(2), positional.get(3)) {
+            // Snippets do not carry the alias table, so they could not
+            // replay what the aliases changed.
+            ("export", Some(_), Some(_)) if aliases_file.is_some() => {
+                Err("cannot capture a run that uses --keyword-aliases".to_string())
+            }
+            ("export", Some(cmd), Some(file)) => snippet::export(cmd, file, &replay_flags, lang),
+            ("import", Some(file), None) => snippet::import(file).map(|matches| {
+                if !matches {
+                    process::exit(1);
+                }
+            }),
+            _ => {
+                usage(&args[0]);
+                process::exit(2);
+            }
+        };
+        if let Err(err) = result {
+            eprintln!("snippet: {}", err);
+            process::exit(1);
+        }
         return;
     }
 
-    let command = &args[1];
-    let filename = &args[2];
+    let aliases = match aliases_file {
+        Some(path) => {
+            let parsed = fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| tokenizer::KeywordAliases::parse(&text));
+            match parsed {
+                Ok(aliases) => aliases,
+                Err(err) => {
+                    eprintln!("Invalid keyword aliases in {}: {}", path, err);
+                    process::exit(2);
+                }
+            }
+        }
+        None => tokenizer::KeywordAliases::default(),
+    };
 
-    let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
-        eprintln!("Failed to read file: {}", filename);
-        String::new()
-    });
+    if command.as_str() == "serve" {
+        let settings = serve::Settings {
+            lang,
+            compat: error_compat,
+            aliases,
+            limits,
+        };
+        if let Some(addr) = http {
+            #[cfg(feature = "server")]
+            if let Err(err) = serve::http::run_http_server(addr, settings) {
+                eprintln!("Cannot serve on {}: {}", addr, err);
+                process::exit(1);
+            }
+            #[cfg(not(feature = "server"))]
+            {
+                eprintln!(
+                    "serve --http {} needs a build with the `server` feature",
+                    addr
+                );
+                process::exit(2);
+            }
+        } else if stdio {
+            serve::run_stdio_server(&settings);
+        } else {
+            eprintln!("serve requires --stdio or --http <addr>");
+            process::exit(2);
+        }
+        return;
+    }
+    if command.as_str() == "conformance" {
+        let Some(reference) = reference else {
+            eprintln!("conformance requires --reference <binary>");
+            process::exit(2);
+        };
+        let corpus = positional.get(1).map_or("tests", |dir| dir.as_str());
+        if !conformance::run_conformance(reference, corpus) {
+            process::exit(1);
+        }
+        return;
+    }
+    if positional.len() < 2 {
+        usage(&args[0]);
+        process::exit(2);
+    }
+
+    let mut sources = source_map::SourceMap::default();
+    for filename in &positional[1..] {
+        let file_contents = fs::read_to_string(filename).unwrap_or_else(|_| {
+            eprintln!("Failed to read file: {}", filename);
+            String::new()
+        });
+        sources.add(filename.as_str(), file_contents);
+    }
+
+    let reporter = diagnostic::Reporter::new(
+        style::Style::detect(no_color),
+        error_format,
+        &sources,
+        lang,
+        error_compat,
+        max_errors,
+    );
 
     match command.as_str() {
-        "tokenize" => tokenizer::run_tokenizer(&file_contents),
-        "parse" => parser::run_parser(&file_contents),
+        "tokenize" => tokenizer::run_tokenizer(&reporter, &aliases, limits),
+        "stats" => stats::run_stats(&reporter, &aliases, limits),
+        "parse" => {
+            if !parser::run_parser(&reporter, &aliases, limits) {
+                process::exit(65);
+            }
+        }
+        "run" => {
+            // Lox calls recurse on the native stack, which the main thread
+            // has too little of.
+            let aliases = &aliases;
+            let outcome = thread::scope(|scope| {
+                thread::Builder::new()
+                    .stack_size(interpreter::STACK_SIZE)
+                    .spawn_scoped(scope, move || {
+                        interpreter::run_program(&reporter, aliases, limits)
+                    })
+                    .unwrap_or_else(|error| {
+                        eprintln!("Could not start the interpreter: {}", error);
+                        process::exit(70);
+                    })
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            });
+            if let Err(failure) = outcome {
+                process::exit(failure.exit_code());
+            }
+        }
         _ => {
             eprintln!("Unknown command: {}", command);
+            process::exit(2);
         }
     }
 }
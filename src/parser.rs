@@ -1 +1,700 @@
-pub fn run_parser(source: &str) {}
+use std::rc::Rc;
+
+use crate::ast::{Expr, FunctionDecl, GlobalSlot, Literal, ScopeDepth, Stmt};
+use crate::diagnostic::{At, Diagnostic, Reporter};
+use crate::line_index::LineIndex;
+use crate::messages::{After, Message, Named};
+use crate::source_map::{SourceFile, SourceId};
+use crate::tokenizer::{KeywordAliases, ReservedWords, ScanLimits, Scanner, Token, TokenType};
+
+#[derive(Debug)]
+pub struct ParseError {
+    pub token: Token,
+    pub message: Message,
+}
+
+impl ParseError {
+    pub fn diagnostic(&self, lines: &LineIndex) -> Diagnostic {
+        let (_, column) = lines.offset_to_line_col(self.token.offset);
+        let at = match self.token.token_type {
+            TokenType::Eof => At::End,
+            _ => At::Lexeme(self.token.lexeme()),
+        };
+        Diagnostic::error(self.token.line, self.message.clone())
+            .in_source(self.token.source)
+            .with_column(column)
+            .at(at)
+    }
+}
+
+/// Recursive-descent parser over the scanner's output, one method per
+/// precedence level of the book's expression grammar. Lexical error tokens
+/// must be filtered out before parsing.
+pub struct Parser {
+    tokens: Vec<Token>,
+    current: usize,
+    /// Errors that do not need to unwind, like an invalid assignment target.
+    errors: Vec<ParseError>,
+    /// How many function bodies enclose the current token.
+    function_depth: usize,
+    /// How many nested expressions and statements are being parsed.
+    nesting: usize,
+}
+
+/// jlox's limit on parameters and arguments, kept for compatibility.
+const MAX_ARGUMENTS: usize = 255;
+
+/// How deeply expressions and statements may nest, counting each operator
+/// of a run like `1 + 2 + 3` as a level. Parsing, resolving, evaluating and
+/// dropping the tree all recurse once per level, so this keeps deep input
+/// from overflowing the stack.
+const MAX_NESTING: usize = 256;
+
+impl Parser {
+    pub fn new(tokens: Vec<Token>) -> Self {
+        Parser {
+            tokens,
+            current: 0,
+            errors: Vec::new(),
+            function_depth: 0,
+            nesting: 0,
+        }
+    }
+
+    pub fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.expression()?;
+        match self.errors.drain(..).next() {
+            Some(error) => Err(error),
+            None => Ok(expr),
+        }
+    }
+
+    /// Parses a whole program. After a syntax error the parser skips to the
+    /// next statement and keeps going, so every error is reported at once.
+    pub fn parse_program(mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() {
+            match self.declaration() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
+            }
+        }
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn declaration(&mut self) -> Result<Stmt, ParseError> {
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Fun))) {
+            return self.function();
+        }
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Var))) {
+            return self.var_declaration();
+        }
+        self.statement()
+    }
+
+    fn function(&mut self) -> Result<Stmt, ParseError> {
+        let name = self
+            .consume(
+                |t| matches!(t, TokenType::Identifier(_)),
+                Message::ExpectName(Named::Function),
+            )?
+            .clone();
+        self.consume(
+            |t| matches!(t, TokenType::LeftParen),
+            Message::ExpectOpeningParen(After::FunctionName),
+        )?;
+        let mut params = Vec::new();
+        if !matches!(self.peek().token_type, TokenType::RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.errors.push(self.error(Message::TooManyParameters));
+                }
+                let param = self.consume(
+                    |t| matches!(t, TokenType::Identifier(_)),
+                    Message::ExpectName(Named::Parameter),
+                )?;
+                params.push(param.clone());
+                if !self.matches(|t| matches!(t, TokenType::Comma)) {
+                    break;
+                }
+            }
+        }
+        self.consume(
+            |t| matches!(t, TokenType::RightParen),
+            Message::ExpectClosingParen(After::Parameters),
+        )?;
+        self.consume(
+            |t| matches!(t, TokenType::LeftBrace),
+            Message::ExpectFunctionBody,
+        )?;
+
+        self.function_depth += 1;
+        let body = self.block();
+        self.function_depth -= 1;
+        Ok(Stmt::Function(Rc::new(FunctionDecl {
+            name,
+            params,
+            body: body?,
+        })))
+    }
+
+    fn var_declaration(&mut self) -> Result<Stmt, ParseError> {
+        let name = self
+            .consume(
+                |t| matches!(t, TokenType::Identifier(_)),
+                Message::ExpectName(Named::Variable),
+            )?
+            .clone();
+        let initializer = if self.matches(|t| matches!(t, TokenType::Equals)) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
+        self.consume(
+            |t| matches!(t, TokenType::Semicolon),
+            Message::ExpectSemicolon(After::VariableDeclaration),
+        )?;
+        Ok(Stmt::Var { name, initializer })
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParseError> {
+        self.nested(Self::plain_statement)
+    }
+
+    fn plain_statement(&mut self) -> Result<Stmt, ParseError> {
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::For))) {
+            return self.for_statement();
+        }
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::If))) {
+            return self.if_statement();
+        }
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Return))) {
+            return self.return_statement();
+        }
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::While))) {
+            return self.while_statement();
+        }
+        if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Print))) {
+            let value = self.expression()?;
+            self.consume(
+                |t| matches!(t, TokenType::Semicolon),
+                Message::ExpectSemicolon(After::Value),
+            )?;
+            return Ok(Stmt::Print(value));
+        }
+        if self.matches(|t| matches!(t, TokenType::LeftBrace)) {
+            return Ok(Stmt::Block(self.block()?));
+        }
+        let expr = self.expression()?;
+        self.consume(
+            |t| matches!(t, TokenType::Semicolon),
+            Message::ExpectSemicolon(After::Expression),
+        )?;
+        Ok(Stmt::Expression(expr))
+    }
+
+    /// Desugars `for (init; condition; increment) body` into
+    /// `{ init; while (condition) { body; increment; } }`.
+    fn for_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(
+            |t| matches!(t, TokenType::LeftParen),
+            Message::ExpectOpeningParen(After::Keyword("for")),
+        )?;
+        let initializer = if self.matches(|t| matches!(t, TokenType::Semicolon)) {
+            None
+        } else if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Var))) {
+            Some(self.var_declaration()?)
+        } else {
+            let expr = self.expression()?;
+            self.consume(
+                |t| matches!(t, TokenType::Semicolon),
+                Message::ExpectSemicolon(After::Expression),
+            )?;
+            Some(Stmt::Expression(expr))
+        };
+        let condition = if matches!(self.peek().token_type, TokenType::Semicolon) {
+            Expr::Literal(Literal::Bool(true))
+        } else {
+            self.expression()?
+        };
+        self.consume(
+            |t| matches!(t, TokenType::Semicolon),
+            Message::ExpectSemicolon(After::LoopCondition),
+        )?;
+        let increment = if matches!(self.peek().token_type, TokenType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            |t| matches!(t, TokenType::RightParen),
+            Message::ExpectClosingParen(After::ForClauses),
+        )?;
+
+        let mut body = self.statement()?;
+        if let Some(increment) = increment {
+            body = Stmt::Block(vec![body, Stmt::Expression(increment)]);
+        }
+        body = Stmt::While {
+            condition,
+            body: Box::new(body),
+        };
+        if let Some(initializer) = initializer {
+            body = Stmt::Block(vec![initializer, body]);
+        }
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(
+            |t| matches!(t, TokenType::LeftParen),
+            Message::ExpectOpeningParen(After::Keyword("if")),
+        )?;
+        let condition = self.expression()?;
+        self.consume(
+            |t| matches!(t, TokenType::RightParen),
+            Message::ExpectClosingParen(After::IfCondition),
+        )?;
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = if self.matches(|t| matches!(t, TokenType::Reserved(ReservedWords::Else)))
+        {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParseError> {
+        let keyword = self.previous().clone();
+        if self.function_depth == 0 {
+            self.errors.push(ParseError {
+                token: keyword,
+                message: Message::TopLevelReturn,
+            });
+        }
+        let value = if matches!(self.peek().token_type, TokenType::Semicolon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+        self.consume(
+            |t| matches!(t, TokenType::Semicolon),
+            Message::ExpectSemicolon(After::ReturnValue),
+        )?;
+        Ok(Stmt::Return(value))
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParseError> {
+        self.consume(
+            |t| matches!(t, TokenType::LeftParen),
+            Message::ExpectOpeningParen(After::Keyword("while")),
+        )?;
+        let condition = self.expression()?;
+        self.consume(
+            |t| matches!(t, TokenType::RightParen),
+            Message::ExpectClosingParen(After::Condition),
+        )?;
+        let body = Box::new(self.statement()?);
+        Ok(Stmt::While { condition, body })
+    }
+
+    /// The statements of a block whose `{` has been consumed.
+    fn block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        let mut statements = Vec::new();
+        while !self.is_at_end() && !matches!(self.peek().token_type, TokenType::RightBrace) {
+            statements.push(self.declaration()?);
+        }
+        self.consume(
+            |t| matches!(t, TokenType::RightBrace),
+            Message::ExpectClosingBrace,
+        )?;
+        Ok(statements)
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParseError> {
+        self.nested(|parser| {
+            let expr = parser.assignment()?;
+            parser.reject_extended()?;
+            Ok(expr)
+        })
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.or()?;
+        if self.matches(|t| matches!(t, TokenType::Equals)) {
+            let equals = self.previous().clone();
+            let value = self.nested(Self::assignment)?;
+            if let Expr::Variable { name, .. } = expr {
+                return Ok(Expr::Assign {
+                    name,
+                    value: Box::new(value),
+                    depth: ScopeDepth::default(),
+                    global: GlobalSlot::default(),
+                });
+            }
+            // Reported without unwinding: the parser is not confused.
+            self.errors.push(ParseError {
+                token: equals,
+                message: Message::InvalidAssignmentTarget,
+            });
+        }
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::and,
+            |t| matches!(t, TokenType::Reserved(ReservedWords::Or)),
+            logical,
+        )
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::equality,
+            |t| matches!(t, TokenType::Reserved(ReservedWords::And)),
+            logical,
+        )
+    }
+
+    fn equality(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::comparison,
+            |t| matches!(t, TokenType::DoubleEquals | TokenType::BangEquals),
+            binary,
+        )
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::term,
+            |t| {
+                matches!(
+                    t,
+                    TokenType::Greater
+                        | TokenType::GreaterEquals
+                        | TokenType::LessThan
+                        | TokenType::LessThanEquals
+                )
+            },
+            binary,
+        )
+    }
+
+    fn term(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::factor,
+            |t| matches!(t, TokenType::Minus | TokenType::Plus),
+            binary,
+        )
+    }
+
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        self.chain(
+            Self::unary,
+            |t| matches!(t, TokenType::Slash | TokenType::Star),
+            binary,
+        )
+    }
+
+    /// A left-associative run of one precedence level, like `a + b - c`.
+    /// Every operator puts the tree one level deeper, so each one counts
+    /// against `MAX_NESTING` until the run ends.
+    fn chain(
+        &mut self,
+        operand: fn(&mut Self) -> Result<Expr, ParseError>,
+        is_operator: fn(&TokenType) -> bool,
+        build: fn(Expr, Token, Expr) -> Expr,
+    ) -> Result<Expr, ParseError> {
+        let mut expr = operand(self)?;
+        let outer = self.nesting;
+        let result = loop {
+            if !self.matches(is_operator) {
+                break Ok(expr);
+            }
+            let op = self.previous().clone();
+            if let Err(error) = self.deepen() {
+                break Err(error);
+            }
+            match operand(self) {
+                Ok(right) => expr = build(expr, op, right),
+                Err(error) => break Err(error),
+            }
+        };
+        self.nesting = outer;
+        result
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        if self.matches(|t| matches!(t, TokenType::Bang | TokenType::Minus)) {
+            let op = self.previous().clone();
+            let right = self.nested(Self::unary)?;
+            return Ok(Expr::Unary {
+                op,
+                right: Box::new(right),
+            });
+        }
+        self.call()
+    }
+
+    /// Calls chain like binary operators: `f()()` calls the result of
+    /// `f()`, one level deeper each time.
+    fn call(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.primary()?;
+        let outer = self.nesting;
+        let result = loop {
+            if !self.matches(|t| matches!(t, TokenType::LeftParen)) {
+                break Ok(expr);
+            }
+            if let Err(error) = self.deepen() {
+                break Err(error);
+            }
+            match self.finish_call(expr) {
+                Ok(call) => expr = call,
+                Err(error) => break Err(error),
+            }
+        };
+        self.nesting = outer;
+        result
+    }
+
+    /// The arguments of a call whose `(` has been consumed.
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParseError> {
+        let mut arguments = Vec::new();
+        if !matches!(self.peek().token_type, TokenType::RightParen) {
+            loop {
+                if arguments.len() >= MAX_ARGUMENTS {
+                    self.errors.push(self.error(Message::TooManyArguments));
+                }
+                arguments.push(self.expression()?);
+                if !self.matches(|t| matches!(t, TokenType::Comma)) {
+                    break;
+                }
+            }
+        }
+        let paren = self
+            .consume(
+                |t| matches!(t, TokenType::RightParen),
+                Message::ExpectClosingParen(After::Arguments),
+            )?
+            .clone();
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            arguments,
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParseError> {
+        self.reject_extended()?;
+        let literal = match &self.peek().token_type {
+            TokenType::Reserved(ReservedWords::False) => Literal::Bool(false),
+            TokenType::Reserved(ReservedWords::True) => Literal::Bool(true),
+            TokenType::Reserved(ReservedWords::Nil) => Literal::Nil,
+            TokenType::Number(val) => Literal::Number(val.parse().unwrap_or_default()),
+            TokenType::String(s) => Literal::String(s.clone()),
+            TokenType::Identifier(_) => {
+                let name = self.advance().clone();
+                return Ok(Expr::Variable {
+                    name,
+                    depth: ScopeDepth::default(),
+                    global: GlobalSlot::default(),
+                });
+            }
+            TokenType::LeftParen => {
+                self.advance();
+                let expr = self.expression()?;
+                self.consume(
+                    |t| matches!(t, TokenType::RightParen),
+                    Message::ExpectClosingParen(After::Expression),
+                )?;
+                return Ok(Expr::Grouping(Box::new(expr)));
+            }
+            _ => return Err(self.error(Message::ExpectExpression)),
+        };
+        self.advance();
+        Ok(Expr::Literal(literal))
+    }
+
+    /// Runs `parse` one level deeper, failing at the current token once the
+    /// input nests past `MAX_NESTING`.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        self.deepen()?;
+        let result = parse(self);
+        self.nesting -= 1;
+        result
+    }
+
+    /// Counts one more level, or fails if that would pass `MAX_NESTING`.
+    fn deepen(&mut self) -> Result<(), ParseError> {
+        if self.nesting >= MAX_NESTING {
+            return Err(self.error(Message::NestingTooDeep));
+        }
+        self.nesting += 1;
+        Ok(())
+    }
+
+    /// The tokens scanned for planned features are errors until the parser
+    /// learns them.
+    fn reject_extended(&self) -> Result<(), ParseError> {
+        match self.peek().token_type {
+            TokenType::Question
+            | TokenType::Colon
+            | TokenType::Percent
+            | TokenType::LeftBracket
+            | TokenType::RightBracket => Err(self.error(Message::RequiresExtended)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Skips tokens until the start of what is probably the next statement.
+    fn synchronize(&mut self) {
+        self.advance();
+        while !self.is_at_end() {
+            if matches!(self.previous().token_type, TokenType::Semicolon) {
+                return;
+            }
+            if let TokenType::Reserved(
+                ReservedWords::Class
+                | ReservedWords::Fun
+                | ReservedWords::Var
+                | ReservedWords::For
+                | ReservedWords::If
+                | ReservedWords::While
+                | ReservedWords::Print
+                | ReservedWords::Return,
+            ) = self.peek().token_type
+            {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    fn matches(&mut self, test: impl Fn(&TokenType) -> bool) -> bool {
+        if test(&self.peek().token_type) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume(
+        &mut self,
+        test: impl Fn(&TokenType) -> bool,
+        message: Message,
+    ) -> Result<&Token, ParseError> {
+        if test(&self.peek().token_type) {
+            return Ok(self.advance());
+        }
+        Err(self.error(message))
+    }
+
+    fn error(&self, message: Message) -> ParseError {
+        ParseError {
+            token: self.peek().clone(),
+            message,
+        }
+    }
+
+    fn is_at_end(&self) -> bool {
+        matches!(self.peek().token_type, TokenType::Eof)
+    }
+
+    fn advance(&mut self) -> &Token {
+        if !self.is_at_end() {
+            self.current += 1;
+        }
+        self.previous()
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current]
+    }
+
+    fn previous(&self) -> &Token {
+        &self.tokens[self.current.saturating_sub(1)]
+    }
+}
+
+fn binary(left: Expr, op: Token, right: Expr) -> Expr {
+    Expr::Binary {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+fn logical(left: Expr, op: Token, right: Expr) -> Expr {
+    Expr::Logical {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+/// Scans a file for the parser. Lexical errors are reported and left out of
+/// the tokens; the flag says whether there were any.
+pub fn scan_for_parser(
+    reporter: &Reporter,
+    id: SourceId,
+    file: &SourceFile,
+    aliases: &KeywordAliases,
+    limits: ScanLimits,
+) -> (Vec<Token>, bool) {
+    let lines = LineIndex::new(&file.text);
+    let mut tokens = Vec::new();
+    let mut lexical_errors = false;
+    for token in Scanner::new(&file.text)
+        .with_aliases(aliases)
+        .with_limits(limits)
+        .in_source(id)
+        .scan_tokens()
+    {
+        match token.located_diagnostic(&lines) {
+            Some(diagnostic) => {
+                reporter.report(&diagnostic);
+                lexical_errors = true;
+            }
+            None => tokens.push(token),
+        }
+    }
+    (tokens, lexical_errors)
+}
+
+/// `parse`: prints each file's expression in the book's parenthesized form.
+/// Returns false if any file had a lexical or syntax error.
+pub fn run_parser(reporter: &Reporter, aliases: &KeywordAliases, limits: ScanLimits) -> bool {
+    let mut ok = true;
+    for (id, file) in reporter.sources.iter() {
+        let lines = LineIndex::new(&file.text);
+        let (tokens, lexical_errors) = scan_for_parser(reporter, id, file, aliases, limits);
+
+        match Parser::new(tokens).parse_expression() {
+            Ok(expr) if !lexical_errors => println!("{}", expr),
+            Ok(_) => ok = false,
+            Err(error) => {
+                reporter.report(&error.diagnostic(&lines));
+                ok = false;
+            }
+        }
+    }
+    reporter.finish();
+    ok
+}
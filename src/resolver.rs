@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use crate::ast::{Expr, FunctionDecl, ScopeDepth, Stmt};
+use crate::messages::Message;
+use crate::parser::ParseError;
+use crate::tokenizer::Token;
+
+/// Static pass between parsing and execution. It works out which scope every
+/// variable reference means and records the distance on the node, so the
+/// interpreter can go straight to that scope. References it cannot find in
+/// any enclosing block or function are globals. Its errors are reported like
+/// syntax errors.
+#[derive(Default)]
+pub struct Resolver {
+    /// One map per local scope, from name to whether its initializer has
+    /// finished. Empty in top-level code, which only declares globals.
+    scopes: Vec<HashMap<String, bool>>,
+    errors: Vec<ParseError>,
+}
+
+impl Resolver {
+    pub fn resolve(mut self, statements: &[Stmt]) -> Result<(), Vec<ParseError>> {
+        self.statements(statements);
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.statement(stmt);
+        }
+    }
+
+    fn statement(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::Print(expr) | Stmt::Expression(expr) => self.expression(expr),
+            Stmt::Var { name, initializer } => {
+                self.declare(name);
+                if let Some(initializer) = initializer {
+                    self.expression(initializer);
+                }
+                self.define(name);
+            }
+            Stmt::Block(statements) => {
+                self.scopes.push(HashMap::new());
+                self.statements(statements);
+                self.scopes.pop();
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition);
+                self.statement(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.statement(else_branch);
+                }
+            }
+            Stmt::While { condition, body } => {
+                self.expression(condition);
+                self.statement(body);
+            }
+            Stmt::Function(decl) => {
+                // Defined before the body is resolved, so it can recurse.
+                self.declare(&decl.name);
+                self.define(&decl.name);
+                self.function(decl);
+            }
+            Stmt::Return(value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+            }
+        }
+    }
+
+    /// The parameters and body share one scope, as they do when the
+    /// function is called.
+    fn function(&mut self, decl: &FunctionDecl) {
+        self.scopes.push(HashMap::new());
+        for param in &decl.params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.statements(&decl.body);
+        self.scopes.pop();
+    }
+
+    fn expression(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::Grouping(inner) => self.expression(inner),
+            Expr::Unary { right, .. } => self.expression(right),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            Expr::Call {
+                callee, arguments, ..
+            } => {
+                self.expression(callee);
+                for argument in arguments {
+                    self.expression(argument);
+                }
+            }
+            Expr::Variable { name, depth, .. } => {
+                let initializing = self
+                    .scopes
+                    .last()
+                    .and_then(|scope| scope.get(name.identifier()))
+                    == Some(&false);
+                if initializing {
+                    self.error(name, Message::ReadInOwnInitializer);
+                }
+                self.local(name, depth);
+            }
+            Expr::Assign {
+                name, value, depth, ..
+            } => {
+                self.expression(value);
+                self.local(name, depth);
+            }
+        }
+    }
+
+    /// Records how many scopes out the variable was declared, if it is a
+    /// local at all.
+    fn local(&mut self, name: &Token, depth: &ScopeDepth) {
+        let found = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name.identifier()));
+        depth.set(found);
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+        if scope.insert(name.identifier().to_string(), false).is_some() {
+            self.error(name, Message::AlreadyDeclared);
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.identifier().to_string(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: Message) {
+        self.errors.push(ParseError {
+            token: token.clone(),
+            message,
+        });
+    }
+}
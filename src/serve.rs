@@ -0,0 +1,420 @@
+use std::io::{self, BufRead, Write};
+
+use crate::diagnostic::Diagnostic;
+use crate::json::{self, JsonValue};
+use crate::line_index::LineIndex;
+use crate::messages::{ErrorCompat, Lang};
+use crate::parser::Parser;
+use crate::resolver::Resolver;
+use crate::tokenizer::{KeywordAliases, ScanLimits, Scanner, Token, TokenType};
+
+/// The command-line options every request is handled with, the same ones
+/// the file-based commands take.
+pub struct Settings {
+    pub lang: Lang,
+    pub compat: ErrorCompat,
+    pub aliases: KeywordAliases,
+    pub limits: ScanLimits,
+}
+
+/// Serves newline-delimited JSON requests on stdin, one JSON response per
+/// line on stdout, so a single warm process can handle many files.
+///
+/// A request looks like `{"cmd": "tokenize", "source": "...", "id": 1}`.
+/// `id` and `file` are optional and are echoed back / used in diagnostics.
+/// `parse` reads `source` as one expression, like the `parse` command, and
+/// `check` reports every lexical, syntax and resolution error in a program
+/// without running it.
+/// `position` converts between a byte `offset` and a 1-based `line`/`column`
+/// in `source`, whichever of the two was given.
+pub fn run_stdio_server(settings: &Settings) {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_request(&line, settings);
+        if writeln!(stdout, "{}", response)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+pub fn handle_request(line: &str, settings: &Settings) -> JsonValue {
+    match json::parse(line) {
+        Ok(request) => respond(&request, settings),
+        Err(err) => failure(JsonValue::Null, format!("invalid request: {}", err)),
+    }
+}
+
+fn respond(request: &JsonValue, settings: &Settings) -> JsonValue {
+    let id = request.get("id").cloned().unwrap_or(JsonValue::Null);
+    let Some(source) = request.get("source").and_then(JsonValue::as_str) else {
+        return failure(id, "request is missing a string 'source'");
+    };
+    let file = request
+        .get("file")
+        .and_then(JsonValue::as_str)
+        .unwrap_or("<request>");
+
+    match request.get("cmd").and_then(JsonValue::as_str) {
+        Some("tokenize") => tokenize(id, source, file, settings),
+        Some("position") => position(id, source, request),
+        Some("parse") => parse(id, source, file, settings),
+        Some("check") => check(id, source, file, settings),
+        Some("run") => failure(id, "run is not offered over serve"),
+        Some(cmd) => failure(id, format!("unknown command: {}", cmd)),
+        None => failure(id, "request is missing a string 'cmd'"),
+    }
+}
+
+fn failure(id: JsonValue, message: impl Into<String>) -> JsonValue {
+    json::object([
+        ("id", id),
+        ("ok", JsonValue::Bool(false)),
+        ("error", json::string(message)),
+    ])
+}
+
+fn position(id: JsonValue, source: &str, request: &JsonValue) -> JsonValue {
+    let number = |key: &str| match request.get(key) {
+        Some(JsonValue::Number(n)) if *n >= 0.0 => Some(*n as usize),
+        _ => None,
+    };
+    let lines = LineIndex::new(source);
+    let (line, column, offset) = match (number("offset"), number("line"), number("column")) {
+        (Some(offset), _, _) => {
+            let (line, column) = lines.offset_to_line_col(offset);
+            (line, column, offset.min(source.len()))
+        }
+        (None, Some(line), Some(column)) => match lines.line_col_to_offset(line, column) {
+            Some(offset) => (line, column, offset),
+            None => return failure(id, "position is outside the source"),
+        },
+        _ => return failure(id, "position needs 'offset' or 'line' and 'column'"),
+    };
+    json::object([
+        ("id", id),
+        ("ok", JsonValue::Bool(true)),
+        ("line", JsonValue::Number(line as f64)),
+        ("column", JsonValue::Number(column as f64)),
+        ("offset", JsonValue::Number(offset as f64)),
+    ])
+}
+
+fn tokenize(id: JsonValue, source: &str, file: &str, settings: &Settings) -> JsonValue {
+    let mut tokens = Vec::new();
+    let mut diagnostics = Vec::new();
+    let lines = LineIndex::new(source);
+    let mut columns = lines.cursor();
+    let scanned = Scanner::new(source)
+        .with_aliases(&settings.aliases)
+        .with_limits(settings.limits)
+        .scan_tokens();
+    for token in scanned {
+        if let Some(diagnostic) = token.located_diagnostic(&lines) {
+            let diagnostic = diagnostic.to_json_value(file, settings.lang, settings.compat);
+            diagnostics.push(diagnostic);
+            continue;
+        }
+        let lexeme = match &token.token_type {
+            TokenType::String(text) | TokenType::Number(text) | TokenType::Identifier(text) => {
+                text.clone()
+            }
+            fixed => fixed.lexeme().unwrap_or_default().to_string(),
+        };
+        tokens.push(json::object([
+            ("type", json::string(token.token_type.name())),
+            ("lexeme", json::string(lexeme)),
+            ("line", JsonValue::Number(token.line as f64)),
+            (
+                "column",
+                JsonValue::Number(columns.column(token.offset) as f64),
+            ),
+        ]));
+    }
+    json::object([
+        ("id", id),
+        ("ok", JsonValue::Bool(diagnostics.is_empty())),
+        ("tokens", JsonValue::Array(tokens)),
+        ("diagnostics", JsonValue::Array(diagnostics)),
+    ])
+}
+
+/// The tokens the parser needs, and the lexical errors left out of them.
+fn scan(source: &str, lines: &LineIndex, settings: &Settings) -> (Vec<Token>, Vec<Diagnostic>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    let scanned = Scanner::new(source)
+        .with_aliases(&settings.aliases)
+        .with_limits(settings.limits)
+        .scan_tokens();
+    for token in scanned {
+        match token.located_diagnostic(lines) {
+            Some(diagnostic) => errors.push(diagnostic),
+            None => tokens.push(token),
+        }
+    }
+    (tokens, errors)
+}
+
+fn diagnostics_json(diagnostics: &[Diagnostic], file: &str, settings: &Settings) -> JsonValue {
+    JsonValue::Array(
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.to_json_value(file, settings.lang, settings.compat))
+            .collect(),
+    )
+}
+
+fn parse(id: JsonValue, source: &str, file: &str, settings: &Settings) -> JsonValue {
+    let lines = LineIndex::new(source);
+    let (tokens, mut diagnostics) = scan(source, &lines, settings);
+    let expression = match Parser::new(tokens).parse_expression() {
+        Ok(expr) if diagnostics.is_empty() => json::string(expr.to_string()),
+        Ok(_) => JsonValue::Null,
+        Err(error) => {
+            diagnostics.push(error.diagnostic(&lines));
+            JsonValue::Null
+        }
+    };
+    json::object([
+        ("id", id),
+        ("ok", JsonValue::Bool(diagnostics.is_empty())),
+        ("expression", expression),
+        (
+            "diagnostics",
+            diagnostics_json(&diagnostics, file, settings),
+        ),
+    ])
+}
+
+/// Everything `run` reports before it starts executing.
+fn check(id: JsonValue, source: &str, file: &str, settings: &Settings) -> JsonValue {
+    let lines = LineIndex::new(source);
+    let (tokens, mut diagnostics) = scan(source, &lines, settings);
+    match Parser::new(tokens).parse_program() {
+        Ok(statements) if diagnostics.is_empty() => {
+            if let Err(errors) = Resolver::default().resolve(&statements) {
+                diagnostics.extend(errors.iter().map(|error| error.diagnostic(&lines)));
+            }
+        }
+        Ok(_) => {}
+        Err(errors) => diagnostics.extend(errors.iter().map(|error| error.diagnostic(&lines))),
+    }
+    json::object([
+        ("id", id),
+        ("ok", JsonValue::Bool(diagnostics.is_empty())),
+        (
+            "diagnostics",
+            diagnostics_json(&diagnostics, file, settings),
+        ),
+    ])
+}
+
+/// `serve --http`: the same requests as `--stdio`, but as `POST /tokenize`,
+/// `POST /parse` or `POST /check` with a JSON body holding `source`. `/run`
+/// is reserved and answers 501 Not Implemented.
+#[cfg(feature = "server")]
+pub mod http {
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::{failure, respond, Settings};
+    use crate::json::{self, JsonValue};
+
+    const MAX_BODY: usize = 1 << 20;
+    /// The request line and headers together.
+    const MAX_HEAD: u64 = 8 << 10;
+    const MAX_CONNECTIONS: usize = 32;
+    /// Parsing and resolving recurse once per nesting level, which needs
+    /// more than the default thread stack at the parser's limit.
+    const STACK_SIZE: usize = 16 << 20;
+    /// How long a connection may take in all, from accepting it to sending
+    /// the last byte of the response.
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Counts a connection as open until it is dropped.
+    struct Slot(Arc<AtomicUsize>);
+
+    impl Drop for Slot {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// A connection that fails any read or write once its deadline has
+    /// passed. Each one may only block for the time that is left, so a client
+    /// trickling bytes cannot keep a connection open past the deadline.
+    struct Deadline {
+        stream: TcpStream,
+        at: Instant,
+    }
+
+    impl Deadline {
+        fn remaining(&self) -> io::Result<Duration> {
+            let left = self.at.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "connection took too long",
+                ));
+            }
+            Ok(left)
+        }
+    }
+
+    impl Read for Deadline {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.stream.set_read_timeout(Some(self.remaining()?))?;
+            self.stream.read(buf)
+        }
+    }
+
+    impl Write for Deadline {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.stream.set_write_timeout(Some(self.remaining()?))?;
+            self.stream.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.stream.flush()
+        }
+    }
+
+    /// Binds `addr`; a bare `:8080` listens on localhost only.
+    pub fn run_http_server(addr: &str, settings: Settings) -> io::Result<()> {
+        let addr = match addr.strip_prefix(':') {
+            Some(port) => format!("127.0.0.1:{}", port),
+            None => addr.to_string(),
+        };
+        let listener = TcpListener::bind(&addr)?;
+        eprintln!("Listening on http://{}", addr);
+        let settings = Arc::new(settings);
+        let open = Arc::new(AtomicUsize::new(0));
+        for stream in listener.incoming().flatten() {
+            if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::SeqCst);
+                // One attempt without blocking, so a client that does not
+                // read cannot stall the accept loop.
+                let _ = stream.set_nonblocking(true);
+                let _ = send(stream, 503, &failure(JsonValue::Null, "server is busy"));
+                continue;
+            }
+            let slot = Slot(Arc::clone(&open));
+            let settings = Arc::clone(&settings);
+            // If the thread cannot start, the connection and slot are
+            // dropped with the closure.
+            let _ = thread::Builder::new()
+                .stack_size(STACK_SIZE)
+                .spawn(move || {
+                    let _slot = slot;
+                    let _ = handle_connection(stream, &settings);
+                });
+        }
+        Ok(())
+    }
+
+    fn handle_connection(stream: TcpStream, settings: &Settings) -> io::Result<()> {
+        let at = Instant::now() + TIMEOUT;
+        let mut reader = BufReader::new(Deadline {
+            stream: stream.try_clone()?,
+            at,
+        });
+
+        let mut head = (&mut reader).take(MAX_HEAD);
+        let mut request_line = String::new();
+        head.read_line(&mut request_line)?;
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+
+        let mut content_length = 0;
+        let mut complete = false;
+        loop {
+            let mut header = String::new();
+            if head.read_line(&mut header)? == 0 {
+                break;
+            }
+            if header.trim().is_empty() {
+                complete = true;
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                if name.trim().eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+            }
+        }
+
+        let (status, body) = if !complete && head.limit() == 0 {
+            (431, failure(JsonValue::Null, "request headers too large"))
+        } else if content_length > MAX_BODY {
+            (413, failure(JsonValue::Null, "request body too large"))
+        } else if method != "POST" {
+            (405, failure(JsonValue::Null, "only POST is supported"))
+        } else {
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body)?;
+            route(&path, &String::from_utf8_lossy(&body), settings)
+        };
+        send(Deadline { stream, at }, status, &body)
+    }
+
+    fn route(path: &str, body: &str, settings: &Settings) -> (u16, JsonValue) {
+        let cmd = match path {
+            "/tokenize" => "tokenize",
+            "/parse" => "parse",
+            "/check" => "check",
+            "/run" => {
+                let message = format!("not implemented: {}", path);
+                return (501, failure(JsonValue::Null, message));
+            }
+            _ => return (404, failure(JsonValue::Null, "not found")),
+        };
+        let mut request = match json::parse(body) {
+            Ok(JsonValue::Object(fields)) => fields,
+            Ok(_) => return (400, failure(JsonValue::Null, "body must be a JSON object")),
+            Err(err) => {
+                let message = format!("invalid request: {}", err);
+                return (400, failure(JsonValue::Null, message));
+            }
+        };
+        request.insert("cmd".to_string(), json::string(cmd));
+        (200, respond(&JsonValue::Object(request), settings))
+    }
+
+    fn send(mut stream: impl Write, status: u16, body: &JsonValue) -> io::Result<()> {
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            501 => "Not Implemented",
+            _ => "Service Unavailable",
+        };
+        let body = body.to_string();
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            reason,
+            body.len(),
+            body
+        )?;
+        stream.flush()
+    }
+}
@@ -0,0 +1,342 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::messages::Lang;
+
+/// A shareable, replayable reproduction: the source, the command and flags it
+/// was run with, and the output it produced. The command runs next to the
+/// source under its own file name, so diagnostics that name the file come
+/// out the same when the snippet is replayed somewhere else.
+///
+/// The format is line-oriented with length-prefixed payloads, so sources and
+/// outputs can contain anything without escaping:
+///
+/// ```text
+/// lox-snippet 2
+/// command tokenize
+/// file example.lox
+/// flags --lang=es
+/// source 6
+/// var a;
+/// stdout 30
+/// ...
+/// stderr 0
+///
+/// ```
+pub struct Snippet {
+    pub command: String,
+    pub file: String,
+    pub flags: Vec<String>,
+    pub source: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+const VERSION: &str = "2";
+
+/// Commands that read a source file and can therefore be captured.
+const REPLAYABLE: &[&str] = &["tokenize", "parse"];
+
+/// The options `export` records, always as `--name=value`. A replayed
+/// snippet may pass nothing else to the binary.
+const RECORDED_OPTIONS: &[&str] = &[
+    "--error-format",
+    "--error-compat",
+    "--max-identifier-length",
+    "--max-literal-length",
+    "--max-tokens",
+    "--max-errors",
+    "--lang",
+];
+
+impl Snippet {
+    pub fn encode(&self) -> String {
+        let mut out = format!("lox-snippet {}\n", VERSION);
+        out.push_str(&format!("command {}\n", self.command));
+        out.push_str(&format!("file {}\n", self.file));
+        out.push_str(&format!("flags {}\n", self.flags.join(" ")));
+        for (name, payload) in [
+            ("source", &self.source),
+            ("stdout", &self.stdout),
+            ("stderr", &self.stderr),
+        ] {
+            out.push_str(&format!("{} {}\n{}\n", name, payload.len(), payload));
+        }
+        out
+    }
+
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let mut rest = text;
+        let mut header = |expected: &str| -> Result<String, String> {
+            let (line, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+            rest = tail;
+            match line.split_once(' ') {
+                Some((name, value)) if name == expected => Ok(value.to_string()),
+                _ if line == expected => Ok(String::new()),
+                _ => Err(format!("expected '{}' line, found '{}'", expected, line)),
+            }
+        };
+        let version = header("lox-snippet")?;
+        if version != VERSION {
+            return Err(format!("unsupported snippet version {}", version));
+        }
+        let command = header("command")?;
+        let file = header("file")?;
+        let flags = header("flags")?
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        let mut payloads = Vec::new();
+        for name in ["source", "stdout", "stderr"] {
+            let (line, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+            let len: usize = match line.split_once(' ') {
+                Some((found, len)) if found == name => len
+                    .parse()
+                    .map_err(|_| format!("invalid length for {}", name))?,
+                _ => return Err(format!("expected '{}' section", name)),
+            };
+            let payload = tail
+                .get(..len)
+                .ok_or_else(|| format!("{} section is truncated", name))?;
+            payloads.push(payload.to_string());
+            rest = tail[len..].strip_prefix('\n').unwrap_or(&tail[len..]);
+        }
+        let [source, stdout, stderr]: [String; 3] = payloads
+            .try_into()
+            .map_err(|_| "missing sections".to_string())?;
+        Ok(Snippet {
+            command,
+            file,
+            flags,
+            source,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+/// Runs the command on `file` from inside `dir`.
+fn run(
+    command: &str,
+    flags: &[String],
+    dir: &Path,
+    file: &str,
+) -> Result<(String, String), String> {
+    let binary = env::current_exe().map_err(|err| err.to_string())?;
+    let output = Command::new(binary)
+        .current_dir(dir)
+        .args(flags)
+        .arg("--no-color")
+        .arg(command)
+        .arg(file)
+        .output()
+        .map_err(|err| err.to_string())?;
+    Ok((
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+/// Rejects any flag `export` would not have recorded, so a snippet cannot
+/// smuggle in other commands or arguments.
+fn check_flags(flags: &[String]) -> Result<(), String> {
+    for flag in flags {
+        let recorded = flag
+            .split_once('=')
+            .is_some_and(|(name, _)| RECORDED_OPTIONS.contains(&name));
+        if !recorded {
+            return Err(format!("cannot replay flag: {}", flag));
+        }
+    }
+    Ok(())
+}
+
+/// The name a replayed source is written under must stay inside the
+/// replay directory and must not read as an option.
+fn check_file_name(name: &str) -> Result<(), String> {
+    let plain = !name.is_empty()
+        && !name.starts_with('-')
+        && !name.contains(['/', '\\'])
+        && name != "."
+        && name != "..";
+    if plain {
+        Ok(())
+    } else {
+        Err(format!("cannot replay file name: {}", name))
+    }
+}
+
+/// A new directory under the system temp directory that only this user
+/// can reach. Creating it fails rather than reusing anything already there.
+fn private_dir() -> Result<PathBuf, String> {
+    let mut builder = fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    for attempt in 0..16 {
+        let name = format!("lox-snippet-{}-{}-{}", process::id(), nanos, attempt);
+        let dir = env::temp_dir().join(name);
+        match builder.create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+    Err("cannot create a temporary directory".to_string())
+}
+
+/// Writes the snippet's source into `dir` and runs it there.
+fn replay(snippet: &Snippet, dir: &Path) -> Result<(String, String), String> {
+    let mut source = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(dir.join(&snippet.file))
+        .map_err(|err| err.to_string())?;
+    source
+        .write_all(snippet.source.as_bytes())
+        .map_err(|err| err.to_string())?;
+    drop(source);
+    run(&snippet.command, &snippet.flags, dir, &snippet.file)
+}
+
+/// `snippet export <command> <file.lox>`: runs the command and prints the
+/// resulting snippet. The language is always recorded, even when it came
+/// from `LOX_LANG`, so the snippet replays the same anywhere.
+pub fn export(command: &str, file: &str, flags: &[String], lang: Lang) -> Result<(), String> {
+    if !REPLAYABLE.contains(&command) {
+        return Err(format!("cannot capture command: {}", command));
+    }
+    let mut flags = flags.to_vec();
+    flags.push(format!("--lang={}", lang.code()));
+    let source = fs::read_to_string(file).map_err(|err| format!("{}: {}", file, err))?;
+    let path = Path::new(file);
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("{}: not a file name", file))?;
+    check_file_name(name)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let (stdout, stderr) = run(command, &flags, dir, name)?;
+    let snippet = Snippet {
+        command: command.to_string(),
+        file: name.to_string(),
+        flags,
+        source,
+        stdout,
+        stderr,
+    };
+    print!("{}", snippet.encode());
+    Ok(())
+}
+
+/// `snippet import <file>`: replays a snippet, prints its output and reports
+/// whether it still matches what was recorded.
+pub fn import(path: &str) -> Result<bool, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let snippet = Snippet::decode(&text)?;
+    if !REPLAYABLE.contains(&snippet.command.as_str()) {
+        return Err(format!("cannot replay command: {}", snippet.command));
+    }
+    check_flags(&snippet.flags)?;
+    check_file_name(&snippet.file)?;
+
+    let dir = private_dir()?;
+    let result = replay(&snippet, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    let (stdout, stderr) = result?;
+
+    print!("{}", stdout);
+    eprint!("{}", stderr);
+    let matches = stdout == snippet.stdout && stderr == snippet.stderr;
+    if !matches {
+        eprintln!("snippet output differs from the recorded output");
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Snippet {
+        Snippet {
+            command: "tokenize".to_string(),
+            file: "example.lox".to_string(),
+            flags: vec!["--lang=es".to_string(), "--max-tokens=3".to_string()],
+            source: "print \"é\";\nstdout 3\n".to_string(),
+            stdout: "\n\n".to_string(),
+            stderr: String::new(),
+        }
+    }
+
+    #[test]
+    fn round_trips_through_encode() {
+        let snippet = sample();
+        let decoded = Snippet::decode(&snippet.encode()).unwrap();
+        assert_eq!(decoded.command, snippet.command);
+        assert_eq!(decoded.file, snippet.file);
+        assert_eq!(decoded.flags, snippet.flags);
+        assert_eq!(decoded.source, snippet.source);
+        assert_eq!(decoded.stdout, snippet.stdout);
+        assert_eq!(decoded.stderr, snippet.stderr);
+    }
+
+    #[test]
+    fn decodes_empty_flags() {
+        let snippet = Snippet {
+            flags: Vec::new(),
+            ..sample()
+        };
+        assert!(Snippet::decode(&snippet.encode()).unwrap().flags.is_empty());
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let text = sample()
+            .encode()
+            .replacen("lox-snippet 2", "lox-snippet 1", 1);
+        assert!(Snippet::decode(&text).is_err());
+    }
+
+    #[test]
+    fn rejects_truncated_payloads() {
+        let text = sample().encode();
+        let cut = text.find("stdout ").unwrap();
+        assert!(Snippet::decode(&text[..cut]).is_err());
+        let header = format!("source {}", sample().source.len());
+        assert!(Snippet::decode(&text.replacen(&header, "source 999", 1)).is_err());
+        // Ends inside the two bytes of `é`.
+        assert!(Snippet::decode(&text.replacen(&header, "source 8", 1)).is_err());
+    }
+
+    #[test]
+    fn replays_only_plain_file_names() {
+        assert!(check_file_name("example.lox").is_ok());
+        for name in ["", ".", "..", "../x.lox", "dir/x.lox", "--lang=es"] {
+            assert!(
+                check_file_name(name).is_err(),
+                "{:?} should be rejected",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn replays_only_recorded_flags() {
+        let flags = |list: &[&str]| list.iter().map(|flag| flag.to_string()).collect::<Vec<_>>();
+        assert!(check_flags(&flags(&["--lang=es", "--error-compat=book"])).is_ok());
+        assert!(check_flags(&flags(&["--lang"])).is_err());
+        assert!(check_flags(&flags(&["run"])).is_err());
+        assert!(check_flags(&flags(&["--reference=/bin/sh"])).is_err());
+    }
+}
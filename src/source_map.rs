@@ -0,0 +1,40 @@
+/// Identifies one loaded source file. Tokens and diagnostics carry it so
+/// every message can name the file it came from, not just the line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SourceId(usize);
+
+pub struct SourceFile {
+    pub path: String,
+    pub text: String,
+}
+
+/// Every source file loaded during a run, addressed by `SourceId`.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn add(&mut self, path: impl Into<String>, text: impl Into<String>) -> SourceId {
+        self.files.push(SourceFile {
+            path: path.into(),
+            text: text.into(),
+        });
+        SourceId(self.files.len() - 1)
+    }
+
+    pub fn path(&self, id: SourceId) -> &str {
+        self.files.get(id.0).map_or("<unknown>", |file| &file.path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SourceId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (SourceId(index), file))
+    }
+}
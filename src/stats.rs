@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use crate::diagnostic::Reporter;
+use crate::line_index::LineIndex;
+use crate::tokenizer::{KeywordAliases, ReservedWords, ScanLimits, Scanner, TokenType};
+
+/// `stats`: quick code metrics computed from the token stream. Declaration
+/// counts come from the introducing keyword and nesting depth from bracket
+/// tokens, so they need no parser.
+pub fn run_stats(reporter: &Reporter, aliases: &KeywordAliases, limits: ScanLimits) {
+    for (id, file) in reporter.sources.iter() {
+        let tokens = Scanner::new(&file.text)
+            .with_aliases(aliases)
+            .with_limits(limits)
+            .in_source(id)
+            .scan_tokens();
+        let lines = LineIndex::new(&file.text);
+
+        let mut kinds: BTreeMap<&'static str, usize> = BTreeMap::new();
+        let (mut vars, mut functions, mut classes) = (0, 0, 0);
+        let (mut depth, mut max_depth) = (0usize, 0usize);
+        let mut errors = 0;
+        for token in &tokens {
+            if let Some(diagnostic) = token.located_diagnostic(&lines) {
+                reporter.report(&diagnostic);
+                errors += 1;
+                continue;
+            }
+            if matches!(token.token_type, TokenType::Eof) {
+                continue;
+            }
+            *kinds.entry(token.token_type.name()).or_default() += 1;
+            match token.token_type {
+                TokenType::Reserved(ReservedWords::Var) => vars += 1,
+                TokenType::Reserved(ReservedWords::Fun) => functions += 1,
+                TokenType::Reserved(ReservedWords::Class) => classes += 1,
+                TokenType::LeftParen | TokenType::LeftBrace | TokenType::LeftBracket => {
+                    depth += 1;
+                    max_depth = max_depth.max(depth);
+                }
+                TokenType::RightParen | TokenType::RightBrace | TokenType::RightBracket => {
+                    depth = depth.saturating_sub(1);
+                }
+                _ => {}
+            }
+        }
+
+        if reporter.sources.len() > 1 {
+            println!("{}:", file.path);
+        }
+        println!("tokens: {}", kinds.values().sum::<usize>());
+        println!("lexical errors: {}", errors);
+        println!("variable declarations: {}", vars);
+        println!("functions: {}", functions);
+        println!("classes: {}", classes);
+        println!("max nesting depth: {}", max_depth);
+        println!("tokens by kind:");
+        for (kind, count) in kinds {
+            println!("  {:<16} {}", kind, count);
+        }
+    }
+    reporter.finish();
+}
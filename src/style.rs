@@ -0,0 +1,60 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+#[derive(Clone, Copy)]
+pub enum Color {
+    Red,
+    Yellow,
+    Green,
+    Cyan,
+}
+
+impl Color {
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => "31",
+            Color::Yellow => "33",
+            Color::Green => "32",
+            Color::Cyan => "36",
+        }
+    }
+}
+
+/// Decides whether output gets ANSI colors. Shared by every subcommand so
+/// `--no-color`, `NO_COLOR` and non-TTY output are handled in one place.
+/// Stdout and stderr are checked separately, since either may be redirected
+/// while the other is still a terminal.
+#[derive(Clone, Copy)]
+pub struct Style {
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Style {
+    pub fn detect(no_color: bool) -> Self {
+        let no_color_env = env::var_os("NO_COLOR").is_some_and(|val| !val.is_empty());
+        let allowed = !no_color && !no_color_env;
+        Style {
+            stdout: allowed && io::stdout().is_terminal(),
+            stderr: allowed && io::stderr().is_terminal(),
+        }
+    }
+
+    /// Colors text bound for stdout.
+    pub fn paint(&self, color: Color, text: &str) -> String {
+        paint(self.stdout, color, text)
+    }
+
+    /// Colors text bound for stderr.
+    pub fn paint_stderr(&self, color: Color, text: &str) -> String {
+        paint(self.stderr, color, text)
+    }
+}
+
+fn paint(enabled: bool, color: Color, text: &str) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", color.code(), text)
+    } else {
+        text.to_string()
+    }
+}
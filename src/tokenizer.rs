@@ -1,64 +1,35 @@
 use std::char;
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
+
+use crate::diagnostic::{Diagnostic, ErrorFormat, Reporter};
+use crate::line_index::LineIndex;
+use crate::messages::{Limit, Message};
+use crate::source_map::SourceId;
+use crate::style::Color;
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(diagnostic) = self.diagnostic() {
+            return write!(f, "{}", diagnostic);
+        }
         match &self.token_type {
-            TokenType::Error(ch, _) => {
-                if ch.eq_ignore_ascii_case(&'"') {
-                    write!(f, "[line {}] Error: Unterminated String", self.line)
-                } else {
-                    write!(
-                        f,
-                        "[line {}] Error: Unexpected character: {}",
-                        self.line, ch
-                    )
-                }
+            TokenType::String(s) => write!(f, "{} {} \" null", self.token_type.name(), s),
+            TokenType::Number(val) => {
+                let num = val.parse::<f64>().unwrap_or_default();
+                write!(f, "{} {} {}", self.token_type.name(), val, num)
             }
-            TokenType::LeftParen => write!(f, "LEFT_PAREN ( null"),
-            TokenType::RightParen => write!(f, "RIGHT_PAREN ) null"),
-            TokenType::LeftBrace => write!(f, "LEFT_BRACE {{ null"),
-            TokenType::RightBrace => write!(f, "RIGHT_BRACE }} null"),
-            TokenType::Comma => write!(f, "COMMA , null"),
-            TokenType::Dot => write!(f, "DOT . null"),
-            TokenType::Minus => write!(f, "MINUS - null"),
-            TokenType::Plus => write!(f, "PLUS + null"),
-            TokenType::Semicolon => write!(f, "SEMICOLON ; null"),
-            TokenType::Star => write!(f, "STAR * null"),
-            TokenType::String(s) => write!(f, "STRING {} \" null", s),
-            TokenType::Eof => write!(f, "EOF  null"),
-            TokenType::DoubleEquals => write!(f, "EQUAL_EQUAL == null"),
-            TokenType::Equals => write!(f, "EQUAL = null"),
-            TokenType::Greater => write!(f, "GREATER > null"),
-            TokenType::GreaterEquals => write!(f, "GREATER_EQUALS >= null"),
-            TokenType::LessThanEquals => write!(f, "LESSTHAN_EQUALS <= null"),
-            TokenType::LessThan => write!(f, "LESSTHAN < null"),
-            TokenType::Bang => write!(f, "NOT ! null"),
-            TokenType::BangEquals => write!(f, "NOT_EQUALS != null"),
-            TokenType::Slash => write!(f, "SLASH / null"),
-            TokenType::Number(val) => match val.parse::<f64>() {
-                Ok(num) => write!(f, "NUMBER {} {}", val, num),
-                Err(_) => write!(
-                    f,
-                    "[line {}] Error: Invalid number literal: {}",
-                    self.line, val
-                ),
-            },
-            TokenType::Identifier(ident) => write!(f, "IDENTIFIER {} null", ident),
-            TokenType::Reserved(reserved_word) => {
-                write!(
-                    f,
-                    "{} {}",
-                    format!("{:?}", reserved_word),
-                    format!("{:?}", reserved_word).to_lowercase()
-                )
+            TokenType::Identifier(ident) => {
+                write!(f, "{} {} null", self.token_type.name(), ident)
             }
+            TokenType::Reserved(reserved_word) => write!(f, "{} null", reserved_word),
+            fixed => write!(f, "{} {} null", fixed.name(), fixed.lexeme().unwrap_or("")),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum TokenType {
     LeftParen,
     RightParen,
@@ -85,55 +56,364 @@ pub enum TokenType {
     Number(String),
     Identifier(String),
     Reserved(ReservedWords),
+    // Scanned ahead of the features that use them (ternary, modulo, lists)
+    // so their rollout only has to touch the parser.
+    Question,
+    Colon,
+    Percent,
+    LeftBracket,
+    RightBracket,
+    TooLarge(Limit, usize), // a ScanLimits bound and its configured maximum
+}
+
+impl TokenType {
+    /// The token name printed by `tokenize`, e.g. `LEFT_PAREN`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TokenType::LeftParen => "LEFT_PAREN",
+            TokenType::RightParen => "RIGHT_PAREN",
+            TokenType::LeftBrace => "LEFT_BRACE",
+            TokenType::RightBrace => "RIGHT_BRACE",
+            TokenType::Comma => "COMMA",
+            TokenType::Dot => "DOT",
+            TokenType::Minus => "MINUS",
+            TokenType::Plus => "PLUS",
+            TokenType::Semicolon => "SEMICOLON",
+            TokenType::Star => "STAR",
+            TokenType::String(_) => "STRING",
+            TokenType::Eof => "EOF",
+            TokenType::Error(_, _) | TokenType::TooLarge(_, _) => "ERROR",
+            TokenType::Equals => "EQUAL",
+            TokenType::DoubleEquals => "EQUAL_EQUAL",
+            TokenType::Greater => "GREATER",
+            TokenType::GreaterEquals => "GREATER_EQUALS",
+            TokenType::LessThan => "LESSTHAN",
+            TokenType::LessThanEquals => "LESSTHAN_EQUALS",
+            TokenType::Bang => "NOT",
+            TokenType::BangEquals => "NOT_EQUALS",
+            TokenType::Slash => "SLASH",
+            TokenType::Number(_) => "NUMBER",
+            TokenType::Identifier(_) => "IDENTIFIER",
+            TokenType::Reserved(reserved) => reserved.name(),
+            TokenType::Question => "QUESTION",
+            TokenType::Colon => "COLON",
+            TokenType::Percent => "PERCENT",
+            TokenType::LeftBracket => "LEFT_BRACKET",
+            TokenType::RightBracket => "RIGHT_BRACKET",
+        }
+    }
+
+    /// The source text of tokens whose spelling never varies.
+    pub fn lexeme(&self) -> Option<&'static str> {
+        let lexeme = match self {
+            TokenType::LeftParen => "(",
+            TokenType::RightParen => ")",
+            TokenType::LeftBrace => "{",
+            TokenType::RightBrace => "}",
+            TokenType::Comma => ",",
+            TokenType::Dot => ".",
+            TokenType::Minus => "-",
+            TokenType::Plus => "+",
+            TokenType::Semicolon => ";",
+            TokenType::Star => "*",
+            TokenType::Eof => "",
+            TokenType::Equals => "=",
+            TokenType::DoubleEquals => "==",
+            TokenType::Greater => ">",
+            TokenType::GreaterEquals => ">=",
+            TokenType::LessThan => "<",
+            TokenType::LessThanEquals => "<=",
+            TokenType::Bang => "!",
+            TokenType::BangEquals => "!=",
+            TokenType::Slash => "/",
+            TokenType::Reserved(reserved) => reserved.lexeme(),
+            TokenType::Question => "?",
+            TokenType::Colon => ":",
+            TokenType::Percent => "%",
+            TokenType::LeftBracket => "[",
+            TokenType::RightBracket => "]",
+            TokenType::String(_)
+            | TokenType::Error(_, _)
+            | TokenType::TooLarge(_, _)
+            | TokenType::Number(_)
+            | TokenType::Identifier(_) => return None,
+        };
+        Some(lexeme)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
 pub enum ReservedWords {
-    AND,
-    CLASS,
-    FOR,
-    FALSE,
-    ELSE,
-    FUN,
-    IF,
-    NIL,
-    OR,
-    PRINT,
-    RETURN,
-    SUPER,
-    THIS,
-    TRUE,
-    VAR,
-    WHILE,
+    And,
+    Class,
+    For,
+    False,
+    Else,
+    Fun,
+    If,
+    Nil,
+    Or,
+    Print,
+    Return,
+    Super,
+    This,
+    True,
+    Var,
+    While,
+}
+
+impl FromStr for ReservedWords {
+    type Err = ();
+
+    fn from_str(word: &str) -> Result<Self, Self::Err> {
+        let reserved = match word {
+            "and" => ReservedWords::And,
+            "class" => ReservedWords::Class,
+            "else" => ReservedWords::Else,
+            "false" => ReservedWords::False,
+            "for" => ReservedWords::For,
+            "fun" => ReservedWords::Fun,
+            "if" => ReservedWords::If,
+            "nil" => ReservedWords::Nil,
+            "or" => ReservedWords::Or,
+            "print" => ReservedWords::Print,
+            "return" => ReservedWords::Return,
+            "super" => ReservedWords::Super,
+            "this" => ReservedWords::This,
+            "true" => ReservedWords::True,
+            "var" => ReservedWords::Var,
+            "while" => ReservedWords::While,
+            _ => return Err(()),
+        };
+        Ok(reserved)
+    }
+}
+
+impl ReservedWords {
+    /// The token name printed by `tokenize`, e.g. `AND`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ReservedWords::And => "AND",
+            ReservedWords::Class => "CLASS",
+            ReservedWords::For => "FOR",
+            ReservedWords::False => "FALSE",
+            ReservedWords::Else => "ELSE",
+            ReservedWords::Fun => "FUN",
+            ReservedWords::If => "IF",
+            ReservedWords::Nil => "NIL",
+            ReservedWords::Or => "OR",
+            ReservedWords::Print => "PRINT",
+            ReservedWords::Return => "RETURN",
+            ReservedWords::Super => "SUPER",
+            ReservedWords::This => "THIS",
+            ReservedWords::True => "TRUE",
+            ReservedWords::Var => "VAR",
+            ReservedWords::While => "WHILE",
+        }
+    }
+
+    /// The source spelling of the keyword, e.g. `and`.
+    pub fn lexeme(&self) -> &'static str {
+        match self {
+            ReservedWords::And => "and",
+            ReservedWords::Class => "class",
+            ReservedWords::For => "for",
+            ReservedWords::False => "false",
+            ReservedWords::Else => "else",
+            ReservedWords::Fun => "fun",
+            ReservedWords::If => "if",
+            ReservedWords::Nil => "nil",
+            ReservedWords::Or => "or",
+            ReservedWords::Print => "print",
+            ReservedWords::Return => "return",
+            ReservedWords::Super => "super",
+            ReservedWords::This => "this",
+            ReservedWords::True => "true",
+            ReservedWords::Var => "var",
+            ReservedWords::While => "while",
+        }
+    }
 }
 
-#[derive(Debug)]
+impl fmt::Display for ReservedWords {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.name(), self.lexeme())
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Token {
     pub token_type: TokenType,
     pub line: usize,
+    /// Byte offset of the token's first character in the source.
+    pub offset: usize,
+    pub source: SourceId,
+}
+
+impl Token {
+    /// The name an identifier token spells, or "" for other tokens.
+    pub fn identifier(&self) -> &str {
+        match &self.token_type {
+            TokenType::Identifier(name) => name,
+            _ => "",
+        }
+    }
+
+    /// The token as it was written in the source. Aliased keywords give the
+    /// canonical spelling.
+    pub fn lexeme(&self) -> String {
+        match &self.token_type {
+            TokenType::String(s) => format!("\"{}\"", s),
+            TokenType::Number(val) | TokenType::Identifier(val) => val.clone(),
+            TokenType::Error(ch, _) => ch.to_string(),
+            other => other.lexeme().unwrap_or_default().to_string(),
+        }
+    }
+
+    /// The lexical error this token stands for, if it is one.
+    pub fn diagnostic(&self) -> Option<Diagnostic> {
+        let message = match &self.token_type {
+            TokenType::Error('"', _) => Message::UnterminatedString,
+            TokenType::Error(ch, _) => Message::UnexpectedCharacter(*ch),
+            TokenType::TooLarge(limit, max) => Message::ProgramTooLarge(*limit, *max),
+            TokenType::Number(val) if val.parse::<f64>().is_err() => {
+                Message::InvalidNumber(val.clone())
+            }
+            _ => return None,
+        };
+        let line = match self.token_type {
+            TokenType::Error(_, line) => line,
+            _ => self.line,
+        };
+        Some(Diagnostic::error(line, message).in_source(self.source))
+    }
+
+    /// Like `diagnostic`, with the column filled in from the token's offset.
+    /// The column is only looked up for tokens that are errors.
+    pub fn located_diagnostic(&self, lines: &LineIndex) -> Option<Diagnostic> {
+        self.diagnostic().map(|diagnostic| {
+            let (_, column) = lines.offset_to_line_col(self.offset);
+            diagnostic.with_column(column)
+        })
+    }
+}
+
+/// Alternative spellings for reserved words, e.g. localized keywords for a
+/// classroom. Loaded from a file of `alias = keyword` lines (`#` starts a
+/// comment). Aliased words scan as the canonical keyword, so everything after
+/// the scanner only ever sees standard Lox.
+#[derive(Default)]
+pub struct KeywordAliases {
+    aliases: HashMap<String, ReservedWords>,
+}
+
+impl KeywordAliases {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut aliases = HashMap::new();
+        for (index, raw) in text.lines().enumerate() {
+            let line = raw.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |msg: &str| format!("line {}: {}", index + 1, msg);
+            let (alias, keyword) = line
+                .split_once('=')
+                .ok_or_else(|| err("expected 'alias = keyword'"))?;
+            let (alias, keyword) = (alias.trim(), keyword.trim());
+            let reserved = keyword
+                .parse::<ReservedWords>()
+                .map_err(|()| err(&format!("'{}' is not a Lox keyword", keyword)))?;
+            let mut chars = alias.chars();
+            let valid = chars.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+                && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            if !valid {
+                return Err(err(&format!("'{}' is not a valid identifier", alias)));
+            }
+            if alias.parse::<ReservedWords>().is_ok() {
+                return Err(err(&format!("'{}' is already a keyword", alias)));
+            }
+            aliases.insert(alias.to_string(), reserved);
+        }
+        Ok(KeywordAliases { aliases })
+    }
+
+    fn get(&self, word: &str) -> Option<ReservedWords> {
+        self.aliases.get(word).copied()
+    }
+}
+
+/// Upper bounds that keep adversarial inputs from growing memory without
+/// limit. Exceeding one produces a "program too large" error token; the token
+/// limit also stops the scan.
+#[derive(Clone, Copy)]
+pub struct ScanLimits {
+    pub max_identifier_len: usize,
+    pub max_literal_len: usize,
+    pub max_tokens: usize,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        ScanLimits {
+            max_identifier_len: 1024,
+            max_literal_len: 16 * 1024 * 1024,
+            max_tokens: 2_000_000,
+        }
+    }
 }
 
 pub struct Scanner<'a> {
     source: &'a str,
+    limits: ScanLimits,
+    source_id: SourceId,
+    aliases: Option<&'a KeywordAliases>,
     tokens: Vec<Token>,
     current: usize,
+    start: usize,
+    string_start: usize,
     line: usize,
     in_string: bool,
     string_buffer: String,
+    string_too_long: bool,
+    /// Set once the token limit is reached, which ends the scan.
+    truncated: bool,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
         Scanner {
             source,
+            limits: ScanLimits::default(),
+            source_id: SourceId::default(),
+            aliases: None,
             tokens: Vec::new(),
             current: 0,
+            start: 0,
+            string_start: 0,
             line: 1,
             in_string: false,
             string_buffer: String::new(),
+            string_too_long: false,
+            truncated: false,
+        }
+    }
+
+    pub fn with_aliases(self, aliases: &'a KeywordAliases) -> Self {
+        Scanner {
+            aliases: Some(aliases),
+            ..self
         }
     }
 
+    pub fn with_limits(self, limits: ScanLimits) -> Self {
+        Scanner { limits, ..self }
+    }
+
+    /// Tags every token with the file it was scanned from.
+    pub fn in_source(self, source_id: SourceId) -> Self {
+        Scanner { source_id, ..self }
+    }
+
     fn skip_line_comment(&mut self) {
         while let Some(ch) = self.peek() {
             if ch == '\n' {
@@ -155,48 +435,40 @@ impl<'a> Scanner<'a> {
     }
 
     fn identifier(&mut self, curr: char) -> TokenType {
-        let mut map: HashMap<&'static str, ReservedWords> = HashMap::new();
-        map.insert("and", ReservedWords::AND);
-        map.insert("class", ReservedWords::CLASS);
-        map.insert("else", ReservedWords::ELSE);
-        map.insert("false", ReservedWords::FALSE);
-        map.insert("for", ReservedWords::FOR);
-        map.insert("fun", ReservedWords::FUN);
-        map.insert("if", ReservedWords::IF);
-        map.insert("nil", ReservedWords::NIL);
-        map.insert("or", ReservedWords::OR);
-        map.insert("print", ReservedWords::PRINT);
-        map.insert("return", ReservedWords::RETURN);
-        map.insert("super", ReservedWords::SUPER);
-        map.insert("this", ReservedWords::THIS);
-        map.insert("true", ReservedWords::TRUE);
-        map.insert("var", ReservedWords::VAR);
-        map.insert("while", ReservedWords::WHILE);
-
         let mut identifier = String::new();
         identifier.push(curr);
+        let max = self.limits.max_identifier_len;
         while let Some(val) = self.peek() {
             if val.is_ascii_alphanumeric() || val == '_' {
-                identifier.push(val);
+                if identifier.len() <= max {
+                    identifier.push(val);
+                }
                 self.advance();
             } else {
                 break;
             }
         }
+        if identifier.len() > max {
+            return TokenType::TooLarge(Limit::IdentifierLength, max);
+        }
 
-        match map.get(identifier.as_str()) {
-            Some(reserved) => TokenType::Reserved(*reserved),
+        let alias = self.aliases.and_then(|aliases| aliases.get(&identifier));
+        match identifier.parse::<ReservedWords>().ok().or(alias) {
+            Some(reserved) => TokenType::Reserved(reserved),
             None => TokenType::Identifier(identifier),
         }
     }
 
-    fn scan_number(&mut self, curr: char) -> String {
+    fn scan_number(&mut self, curr: char) -> TokenType {
         let mut number = String::new();
         number.push(curr);
+        let max = self.limits.max_literal_len;
 
         while let Some(ch) = self.peek() {
             if ch.is_ascii_digit() {
-                number.push(ch);
+                if number.len() <= max {
+                    number.push(ch);
+                }
                 self.advance();
             } else {
                 break;
@@ -209,7 +481,9 @@ impl<'a> Scanner<'a> {
 
                 while let Some(ch) = self.peek() {
                     if ch.is_ascii_digit() {
-                        number.push(ch);
+                        if number.len() <= max {
+                            number.push(ch);
+                        }
                         self.advance();
                     } else {
                         break;
@@ -217,7 +491,10 @@ impl<'a> Scanner<'a> {
                 }
             }
         }
-        return number;
+        if number.len() > max {
+            return TokenType::TooLarge(Limit::LiteralLength, max);
+        }
+        TokenType::Number(number)
     }
 
     fn peek_next(&self) -> Option<char> {
@@ -239,9 +516,22 @@ impl<'a> Scanner<'a> {
     }
 
     pub fn scan_tokens(mut self) -> Vec<Token> {
-        while !self.at_end() {
+        while !self.truncated && !self.at_end() {
+            self.start = self.current;
             let ch = self.advance().unwrap();
             match ch {
+                // Inside a string everything up to the closing quote is content,
+                // including characters that would otherwise start tokens.
+                _ if self.in_string && ch != '"' => {
+                    if ch == '\n' {
+                        self.line += 1;
+                    }
+                    if self.string_buffer.len() + ch.len_utf8() > self.limits.max_literal_len {
+                        self.string_too_long = true;
+                    } else {
+                        self.string_buffer.push(ch);
+                    }
+                }
                 '\n' => {
                     self.line += 1;
                 }
@@ -255,6 +545,11 @@ impl<'a> Scanner<'a> {
                 '+' => self.push(TokenType::Plus),
                 '*' => self.push(TokenType::Star),
                 ';' => self.push(TokenType::Semicolon),
+                '?' => self.push(TokenType::Question),
+                ':' => self.push(TokenType::Colon),
+                '%' => self.push(TokenType::Percent),
+                '[' => self.push(TokenType::LeftBracket),
+                ']' => self.push(TokenType::RightBracket),
                 '=' => {
                     if self.match_next('=') {
                         self.push(TokenType::DoubleEquals);
@@ -285,12 +580,18 @@ impl<'a> Scanner<'a> {
                 }
                 '"' => {
                     self.in_string = !self.in_string;
-                    if !self.in_string {
-                        self.tokens.push(Token {
-                            token_type: TokenType::String(self.string_buffer.clone()),
-                            line: self.line,
-                        });
+                    if self.in_string {
+                        self.string_start = self.start;
+                    } else {
+                        self.start = self.string_start;
+                        if self.string_too_long {
+                            let max = self.limits.max_literal_len;
+                            self.push(TokenType::TooLarge(Limit::LiteralLength, max));
+                        } else {
+                            self.push(TokenType::String(self.string_buffer.clone()));
+                        }
                         self.string_buffer.clear();
+                        self.string_too_long = false;
                     }
                 }
                 '/' => {
@@ -301,53 +602,82 @@ impl<'a> Scanner<'a> {
                     }
                 }
                 _ if ch.is_ascii_digit() => {
-                    let val = self.scan_number(ch);
-                    self.tokens.push(Token {
-                        token_type: TokenType::Number(val),
-                        line: self.line,
-                    });
-                }
-
-                _ if self.in_string => {
-                    self.string_buffer.push(ch);
+                    let number = self.scan_number(ch);
+                    self.push(number);
                 }
                 _ if ch.is_whitespace() => {}
                 _ if ch.is_ascii_alphabetic() => {
                     let ident = self.identifier(ch);
-                    self.tokens.push(Token {
-                        token_type: ident,
-                        line: self.line,
-                    });
+                    self.push(ident);
                 }
-                _ => self.tokens.push(Token {
-                    token_type: TokenType::Error(ch, self.line),
-                    line: self.line,
-                }),
+                _ => self.push(TokenType::Error(ch, self.line)),
             }
         }
+        self.start = self.current;
         if self.in_string {
-            self.tokens.push(Token {
-                token_type: TokenType::Error('"', self.line),
-                line: self.line,
-            });
+            self.push(TokenType::Error('"', self.line));
         }
         self.push(TokenType::Eof);
         self.tokens
     }
 
+    /// Adds a token, or a `TooLarge` error in its place if the program
+    /// already has `max_tokens`, which also ends the scan. The final EOF does
+    /// not count towards the limit.
     fn push(&mut self, kind: TokenType) {
+        let full = self.tokens.len() >= self.limits.max_tokens;
+        let kind = if full && !matches!(kind, TokenType::Eof) {
+            self.truncated = true;
+            self.in_string = false;
+            TokenType::TooLarge(Limit::TokenCount, self.limits.max_tokens)
+        } else {
+            kind
+        };
         self.tokens.push(Token {
             token_type: kind,
             line: self.line,
+            offset: self.start,
+            source: self.source_id,
         });
     }
 }
 
-pub fn run_tokenizer(source: &str) {
-    let scanner = Scanner::new(source);
-    let tokens = scanner.scan_tokens();
+pub fn run_tokenizer(reporter: &Reporter, aliases: &KeywordAliases, limits: ScanLimits) {
+    for (id, file) in reporter.sources.iter() {
+        let tokens = Scanner::new(&file.text)
+            .with_aliases(aliases)
+            .with_limits(limits)
+            .in_source(id)
+            .scan_tokens();
+        print_tokens(&file.text, tokens, reporter);
+    }
+    reporter.finish();
+}
+
+fn print_tokens(source: &str, tokens: Vec<Token>, reporter: &Reporter) {
+    let style = reporter.style;
+    let lines = LineIndex::new(source);
 
     for token in tokens {
-        println!("{}", token);
+        let line = match token.located_diagnostic(&lines) {
+            Some(diagnostic) => match reporter.format {
+                ErrorFormat::Json => {
+                    reporter.report(&diagnostic);
+                    continue;
+                }
+                ErrorFormat::Human if !reporter.admit() => continue,
+                ErrorFormat::Human => reporter.render(&diagnostic),
+            },
+            None => token.to_string(),
+        };
+        match token.token_type {
+            TokenType::Error(_, _) | TokenType::TooLarge(_, _) => {
+                println!("{}", style.paint(Color::Red, &line))
+            }
+            TokenType::String(_) => println!("{}", style.paint(Color::Green, &line)),
+            TokenType::Number(_) => println!("{}", style.paint(Color::Cyan, &line)),
+            TokenType::Reserved(_) => println!("{}", style.paint(Color::Yellow, &line)),
+            _ => println!("{}", line),
+        }
     }
 }
@@ -0,0 +1,100 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ast::{FunctionDecl, Literal};
+use crate::environment::Environment;
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Callable(Rc<Callable>),
+}
+
+/// Anything that can be called: functions declared in Lox and the built-in
+/// ones provided by the interpreter.
+pub enum Callable {
+    Native {
+        arity: usize,
+        function: fn(&[Value]) -> Value,
+    },
+    Function {
+        decl: Rc<FunctionDecl>,
+        /// The scope the declaration ran in, which the body can see; `None`
+        /// for functions declared at the top level.
+        closure: Option<Rc<RefCell<Environment>>>,
+    },
+}
+
+/// Leaves out the closure, which can refer back to the function itself.
+impl fmt::Debug for Callable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Callable::Native { arity, .. } => {
+                f.debug_struct("Native").field("arity", arity).finish()
+            }
+            Callable::Function { decl, .. } => f.debug_tuple("Function").field(decl).finish(),
+        }
+    }
+}
+
+impl Callable {
+    pub fn arity(&self) -> usize {
+        match self {
+            Callable::Native { arity, .. } => *arity,
+            Callable::Function { decl, .. } => decl.params.len(),
+        }
+    }
+}
+
+/// Functions are only equal to themselves.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Callable(a), Value::Callable(b)) => Rc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Value {
+    /// Lox truthiness: only `nil` and `false` are falsey.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::Number(num) => Value::Number(*num),
+            Literal::String(s) => Value::String(s.clone()),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Nil => Value::Nil,
+        }
+    }
+}
+
+/// How `print` shows a value. Whole numbers print without a fractional
+/// part, like jlox.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Nil => write!(f, "nil"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(num) => write!(f, "{}", num),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Callable(callable) => match callable.as_ref() {
+                Callable::Native { .. } => write!(f, "<native fn>"),
+                Callable::Function { decl, .. } => write!(f, "<fn {}>", decl.name.lexeme()),
+            },
+        }
+    }
+}